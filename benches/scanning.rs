@@ -0,0 +1,59 @@
+//! Benchmarks das funcoes de varredura. Rodam contra o sistema real, entao os
+//! numeros so sao comparaveis na mesma maquina. Para medir uma mudanca, salve
+//! um baseline antes (`cargo bench -- --save-baseline antes`) e compare depois
+//! (`cargo bench -- --baseline antes`). Benchmarks de apps que nao estao
+//! instalados sao pulados.
+
+use std::path::Path;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use mac_app_remover::*;
+
+fn bench_get_installed_apps(c: &mut Criterion) {
+    c.bench_function("get_installed_apps", |b| b.iter(get_installed_apps));
+}
+
+fn bench_get_installed_app_infos(c: &mut Criterion) {
+    c.bench_function("get_installed_app_infos", |b| {
+        b.iter(get_installed_app_infos)
+    });
+}
+
+fn bench_find_related_files(c: &mut Criterion) {
+    if !Path::new("/Applications/Safari.app").exists() {
+        eprintln!("Safari nao encontrado; pulando find_related_files");
+        return;
+    }
+    c.bench_function("find_related_files", |b| {
+        b.iter(|| find_related_files("Safari", Some("com.apple.Safari")))
+    });
+}
+
+fn bench_dir_size(c: &mut Criterion) {
+    let xcode = Path::new("/Applications/Xcode.app");
+    if !xcode.exists() {
+        eprintln!("Xcode nao encontrado; pulando dir_size");
+        return;
+    }
+    let mut group = c.benchmark_group("dir_size");
+    // Uma passada no Xcode leva segundos; o minimo do criterion ja basta.
+    group.sample_size(10);
+    group.bench_function("Xcode", |b| b.iter(|| dir_size(xcode)));
+    group.finish();
+}
+
+fn bench_get_all_bundle_ids_fast(c: &mut Criterion) {
+    c.bench_function("get_all_bundle_ids_fast", |b| {
+        b.iter(get_all_bundle_ids_fast)
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_get_installed_apps,
+    bench_get_installed_app_infos,
+    bench_find_related_files,
+    bench_dir_size,
+    bench_get_all_bundle_ids_fast
+);
+criterion_main!(benches);
@@ -1,9 +1,16 @@
 use eframe::egui;
 use mac_app_remover::*;
-use std::path::PathBuf;
-use std::sync::mpsc;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
 
+/// Icone usado na lista e enquanto o icone do app nao carrega.
+const GENERIC_ICON: &str =
+    "/System/Library/CoreServices/CoreTypes.bundle/Contents/Resources/GenericApplicationIcon.icns";
+/// Lado, em pixels, dos icones carregados.
+const ICON_SIZE: u32 = 128;
+
 fn main() -> eframe::Result<()> {
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
@@ -18,20 +25,39 @@ fn main() -> eframe::Result<()> {
     )
 }
 
-/// Arquivo residual com tamanho pre-calculado.
-struct RelatedFile {
-    path: PathBuf,
-    size: u64,
-}
-
 /// Detalhes do app selecionado.
 struct SelectedDetails {
     name: String,
     path: PathBuf,
     size: u64,
     bundle_id: Option<String>,
+    is_system: bool,
+    /// Roda no App Sandbox: todos os dados ficam no container do app. Lido
+    /// junto com o plano, porque depende do `codesign`.
+    is_sandboxed: bool,
     related: Vec<RelatedFile>,
     total_size: u64,
+    /// `total_size` ainda e uma estimativa (o tamanho do app, depois o `du`); os
+    /// arquivos residuais estao sendo calculados.
+    loading: bool,
+    /// Processos do app em execucao (PID, executavel), incluindo helpers.
+    processes: Vec<(libc::pid_t, PathBuf)>,
+    /// Plano de remocao, disponivel quando `loading` termina.
+    plan: Option<RemovalPlan>,
+    /// ID na App Store, para apps instalados por ela.
+    mas_app_id: Option<u64>,
+    /// Recursos protegidos que o app pode pedir (camera, microfone...).
+    privacy: Vec<String>,
+    /// Esquemas de URL registrados pelo app (`slack`, `zoommtg`...).
+    url_schemes: Vec<String>,
+    /// Tipos de arquivo que o app declara abrir.
+    document_types: Vec<DocumentType>,
+    /// Quais caminhos do plano o usuario consegue apagar.
+    permissions: Option<PermissionReport>,
+    /// Tamanho de `Containers/<bundle-id>/Data/tmp`, se existir.
+    tmp_size: Option<u64>,
+    /// Tamanho de `Saved Application State/<bundle-id>.savedState`, se existir.
+    saved_state_size: Option<u64>,
 }
 
 struct App {
@@ -39,6 +65,8 @@ struct App {
     apps: Vec<AppInfo>,
     /// Texto da barra de busca.
     search_query: String,
+    /// Aba ativa: apps do usuario ou apps de sistema (/System/Applications).
+    show_system: bool,
     /// Indice do app selecionado na lista filtrada.
     selected_index: Option<usize>,
     /// Detalhes do app selecionado (carregados sob demanda).
@@ -49,8 +77,42 @@ struct App {
     log_rx: Option<mpsc::Receiver<LogMsg>>,
     /// Flag para indicar que a remocao esta em andamento.
     removing: bool,
+    /// Opcoes usadas em `execute_plan`; `--rebuild-launch-services` liga a
+    /// reconstrucao do banco do Launch Services.
+    remove_opts: RemovalOptions,
     /// Flag para mostrar dialogo de confirmacao.
     show_confirm: bool,
+    /// Inclui apps em subpastas de /Applications (ex: Utilities).
+    recursive: bool,
+    /// Canal para receber os arquivos residuais calculados em segundo plano.
+    details_rx: Option<mpsc::Receiver<DetailsMsg>>,
+    /// Lista os arquivos residuais por caminho em vez de por tamanho.
+    related_by_path: bool,
+    /// Indice em `apps` -> bundle ID compartilhado com outro app.
+    duplicate_ids: HashMap<usize, String>,
+    /// Icones ja carregados (`None` se o app nao tem `.icns`), por caminho do
+    /// app ou do `.icns`. Preenchido pelas threads de `request_icon`.
+    icon_cache: Arc<Mutex<HashMap<PathBuf, Option<egui::ColorImage>>>>,
+    /// Texturas criadas a partir de `icon_cache`.
+    icon_textures: HashMap<PathBuf, egui::TextureHandle>,
+    /// Avisa que um icone terminou de carregar.
+    icon_tx: mpsc::Sender<PathBuf>,
+    icon_rx: mpsc::Receiver<PathBuf>,
+    /// Icones pedidos que ainda nao chegaram.
+    icons_loading: usize,
+}
+
+/// Resultados do calculo em segundo plano para o app em `path`, nessa ordem.
+enum DetailsMsg {
+    /// Estimativa rapida do `du` (ver `estimate_removal_size`).
+    Estimate { path: PathBuf, total_size: u64 },
+    Plan {
+        path: PathBuf,
+        plan: Option<RemovalPlan>,
+        is_sandboxed: bool,
+        tmp_size: Option<u64>,
+        saved_state_size: Option<u64>,
+    },
 }
 
 enum LogMsg {
@@ -60,75 +122,217 @@ enum LogMsg {
 
 impl App {
     fn new() -> Self {
-        let apps = get_installed_app_infos();
-        Self {
+        let apps = get_installed_app_infos_opts(true, false);
+        let duplicate_ids = duplicates_by_index(&apps);
+        let (icon_tx, icon_rx) = mpsc::channel();
+        let mut app = Self {
             apps,
+            duplicate_ids,
+            icon_cache: Arc::default(),
+            icon_textures: HashMap::new(),
+            icon_tx,
+            icon_rx,
+            icons_loading: 0,
             search_query: String::new(),
+            show_system: false,
             selected_index: None,
             selected_details: None,
             log_messages: Vec::new(),
             log_rx: None,
             removing: false,
+            remove_opts: RemovalOptions {
+                rebuild_launch_services: std::env::args().any(|a| a == "--rebuild-launch-services"),
+                ..Default::default()
+            },
             show_confirm: false,
+            recursive: false,
+            details_rx: None,
+            related_by_path: false,
+        };
+        app.request_icon(PathBuf::from(GENERIC_ICON));
+        app
+    }
+
+    /// Carrega o icone de `path` (um `.app` ou um `.icns`) em segundo plano, uma vez.
+    fn request_icon(&mut self, path: PathBuf) {
+        if self.icon_cache.lock().unwrap().contains_key(&path) {
+            return;
+        }
+        self.icons_loading += 1;
+        let cache = Arc::clone(&self.icon_cache);
+        let tx = self.icon_tx.clone();
+        thread::spawn(move || {
+            let rgba = if path.extension().is_some_and(|e| e == "icns") {
+                load_icns_rgba(&path, ICON_SIZE)
+            } else {
+                load_app_icon_rgba(&path, ICON_SIZE)
+            };
+            let image = rgba.map(|(width, height, pixels)| {
+                egui::ColorImage::from_rgba_unmultiplied([width as usize, height as usize], &pixels)
+            });
+            cache.lock().unwrap().insert(path.clone(), image);
+            let _ = tx.send(path);
+        });
+    }
+
+    fn poll_icons(&mut self) {
+        while self.icon_rx.try_recv().is_ok() {
+            self.icons_loading -= 1;
         }
     }
 
+    /// Textura do icone de `path`, se ja carregou.
+    fn icon_texture(&mut self, ctx: &egui::Context, path: &Path) -> Option<egui::TextureHandle> {
+        if let Some(texture) = self.icon_textures.get(path) {
+            return Some(texture.clone());
+        }
+        let image = self.icon_cache.lock().unwrap().get(path)?.clone()?;
+        let texture = ctx.load_texture(
+            path.display().to_string(),
+            image,
+            egui::TextureOptions::LINEAR,
+        );
+        self.icon_textures
+            .insert(path.to_path_buf(), texture.clone());
+        Some(texture)
+    }
+
     fn reload_apps(&mut self) {
-        self.apps = get_installed_app_infos();
+        self.apps = get_installed_app_infos_opts(true, self.recursive);
+        self.duplicate_ids = duplicates_by_index(&self.apps);
         self.selected_index = None;
         self.selected_details = None;
     }
 
     fn filtered_apps(&self) -> Vec<usize> {
-        if self.search_query.is_empty() {
-            return (0..self.apps.len()).collect();
-        }
         let q = self.search_query.to_lowercase();
         self.apps
             .iter()
             .enumerate()
-            .filter(|(_, app)| app.name.to_lowercase().contains(&q))
+            .filter(|(_, app)| app.is_system == self.show_system)
+            .filter(|(_, app)| q.is_empty() || app.name.to_lowercase().contains(&q))
             .map(|(i, _)| i)
             .collect()
     }
 
     fn select_app(&mut self, global_index: usize) {
         let app = &self.apps[global_index];
-        let related_paths = find_related_files(&app.name, app.bundle_id.as_deref());
-        let mut total = app.size;
-        let related: Vec<RelatedFile> = related_paths
-            .into_iter()
-            .map(|path| {
-                let size = if path.is_dir() {
-                    dir_size(&path).unwrap_or(0)
-                } else {
-                    std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0)
-                };
-                total += size;
-                RelatedFile { path, size }
-            })
-            .collect();
+        let processes = app
+            .bundle_id
+            .as_deref()
+            .map(get_app_processes)
+            .unwrap_or_default();
+
+        // `du` e dir_size dos residuais podem demorar: calcula fora da thread da UI.
+        let (tx, rx) = mpsc::channel();
+        let path = app.path.clone();
+        let bundle_id = app.bundle_id.clone();
+        thread::spawn(move || {
+            let _ = tx.send(DetailsMsg::Estimate {
+                path: path.clone(),
+                total_size: estimate_removal_size(&path),
+            });
+            let plan = plan_removal_at(&path, &RemovalOptions::default(), &Config::default()).ok();
+            let is_sandboxed = is_app_sandboxed(&path);
+            let tmp_size = bundle_id
+                .as_deref()
+                .and_then(find_container_tmp)
+                .map(|tmp| dir_size(&tmp).unwrap_or(0));
+            let saved_state_size = bundle_id
+                .as_deref()
+                .and_then(find_saved_state)
+                .map(|state| dir_size(&state).unwrap_or(0));
+            let _ = tx.send(DetailsMsg::Plan {
+                path,
+                plan,
+                is_sandboxed,
+                tmp_size,
+                saved_state_size,
+            });
+        });
+        self.details_rx = Some(rx);
+        let app_path = app.path.clone();
 
         self.selected_details = Some(SelectedDetails {
             name: app.name.clone(),
             path: app.path.clone(),
             size: app.size,
             bundle_id: app.bundle_id.clone(),
-            related,
-            total_size: total,
+            is_system: app.is_system,
+            is_sandboxed: false,
+            related: Vec::new(),
+            total_size: app.size,
+            loading: true,
+            processes,
+            plan: None,
+            mas_app_id: is_mas_app(&app.path)
+                .then(|| get_mas_app_id(&app.path))
+                .flatten(),
+            privacy: app.privacy_permissions(),
+            url_schemes: get_app_url_schemes(&app.path),
+            document_types: get_app_document_types(&app.path),
+            permissions: None,
+            tmp_size: None,
+            saved_state_size: None,
         });
+        self.request_icon(app_path);
+    }
+
+    fn poll_details(&mut self) {
+        loop {
+            let msg = match self.details_rx.as_ref().map(|rx| rx.try_recv()) {
+                Some(Ok(msg)) => msg,
+                Some(Err(mpsc::TryRecvError::Empty)) | None => return,
+                Some(Err(mpsc::TryRecvError::Disconnected)) => {
+                    self.details_rx = None;
+                    return;
+                }
+            };
+            let Some(details) = &mut self.selected_details else {
+                continue;
+            };
+            match msg {
+                // Ignora resultados de um app que ja nao esta selecionado.
+                DetailsMsg::Estimate { path, total_size } if details.path == path => {
+                    details.total_size = total_size.max(details.size);
+                }
+                DetailsMsg::Plan {
+                    path,
+                    plan,
+                    is_sandboxed,
+                    tmp_size,
+                    saved_state_size,
+                } if details.path == path => {
+                    self.details_rx = None;
+                    if let Some(plan) = &plan {
+                        details.size = plan.app_size;
+                        details.total_size = plan.total_bytes;
+                        details.related = plan.related.clone();
+                    }
+                    details.permissions = plan.as_ref().map(check_removal_permissions);
+                    details.is_sandboxed = is_sandboxed;
+                    details.tmp_size = tmp_size;
+                    details.saved_state_size = saved_state_size;
+                    details.plan = plan;
+                    details.loading = false;
+                    return;
+                }
+                _ => {}
+            }
+        }
     }
 
     fn start_removal(&mut self) {
-        let details = match &self.selected_details {
-            Some(d) => d,
-            None => return,
+        let (plan, app_name) = match &self.selected_details {
+            Some(SelectedDetails {
+                plan: Some(plan),
+                name,
+                ..
+            }) => (plan.clone(), name.clone()),
+            _ => return,
         };
 
-        let app_path = details.path.clone();
-        let app_name = details.name.clone();
-        let related_paths: Vec<PathBuf> = details.related.iter().map(|r| r.path.clone()).collect();
-
+        let opts = self.remove_opts.clone();
         let (tx, rx) = mpsc::channel();
         self.log_rx = Some(rx);
         self.removing = true;
@@ -136,56 +340,33 @@ impl App {
         self.show_confirm = false;
 
         thread::spawn(move || {
-            // Verificar se o app esta em execucao e tentar fechar
-            if is_app_running(&app_name) {
-                let _ = tx.send(LogMsg::Line(format!(
-                    "\"{}\" esta em execucao, tentando fechar...",
-                    app_name
-                )));
-                quit_app(&app_name);
-                thread::sleep(std::time::Duration::from_secs(2));
-            }
-
-            let _ = tx.send(LogMsg::Line(format!(
-                "Removendo {}...",
-                app_path.display()
-            )));
-            match remove_path(&app_path) {
-                Ok(_) => {
-                    let _ = tx.send(LogMsg::Line(format!(
-                        "  {} - OK",
-                        app_path.display()
-                    )));
-                }
-                Err(e) => {
-                    let _ = tx.send(LogMsg::Line(format!(
-                        "  {} - ERRO: {}",
-                        app_path.display(),
-                        e
-                    )));
-                }
-            }
-
-            for path in &related_paths {
-                let _ = tx.send(LogMsg::Line(format!("Removendo {}...", path.display())));
-                match remove_path(path) {
-                    Ok(_) => {
-                        let _ = tx.send(LogMsg::Line(format!("  {} - OK", path.display())));
+            let log = |line: String| {
+                let _ = tx.send(LogMsg::Line(line));
+            };
+            match execute_plan(&plan, &opts, |p| log(p.to_string())) {
+                Ok(result) => {
+                    for note in &result.notes {
+                        log(format!("Aviso: {}", note));
+                    }
+                    if !result.removed.is_empty() {
+                        let record =
+                            RemovalRecord::new(&result, plan.bundle_id.clone(), opts.strategy);
+                        if let Err(e) = append_removal_record(record) {
+                            log(format!("Aviso: historico nao foi salvo: {}", e));
+                        }
                     }
-                    Err(e) => {
-                        let _ = tx.send(LogMsg::Line(format!(
-                            "  {} - ERRO: {}",
-                            path.display(),
-                            e
-                        )));
+                    if result.is_success() {
+                        log(format!("\n\"{}\" removido com sucesso!", app_name));
+                    } else {
+                        log(format!(
+                            "\n\"{}\" removido com {} erro(s).",
+                            app_name,
+                            result.errors.len()
+                        ));
                     }
                 }
+                Err(e) => log(format!("{}.", e)),
             }
-
-            let _ = tx.send(LogMsg::Line(format!(
-                "\n\"{}\" removido com sucesso!",
-                app_name
-            )));
             let _ = tx.send(LogMsg::Done);
         });
     }
@@ -217,12 +398,27 @@ impl App {
 impl eframe::App for App {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         self.poll_log();
+        self.poll_details();
+        self.poll_icons();
 
-        // Solicitar repaint enquanto estiver removendo para atualizar o log.
-        if self.removing {
+        // Solicitar repaint enquanto estiver removendo, calculando tamanhos ou carregando icones.
+        if self.removing || self.details_rx.is_some() || self.icons_loading > 0 {
             ctx.request_repaint();
         }
 
+        let generic_icon = self.icon_texture(ctx, Path::new(GENERIC_ICON));
+        let selected_path = self.selected_details.as_ref().map(|d| d.path.clone());
+        // O icone do app, ou o generico se o app nao tiver `.icns`.
+        let selected_icon = selected_path.and_then(|path| {
+            let loaded = self.icon_cache.lock().unwrap().contains_key(&path);
+            let icon = self.icon_texture(ctx, &path);
+            if loaded {
+                Some(icon.or_else(|| generic_icon.clone()))
+            } else {
+                None
+            }
+        });
+
         // Painel superior: barra de busca
         egui::TopBottomPanel::top("search_bar").show(ctx, |ui| {
             ui.add_space(6.0);
@@ -240,6 +436,12 @@ impl eframe::App for App {
                 if ui.button("Recarregar").clicked() {
                     self.reload_apps();
                 }
+                if ui
+                    .checkbox(&mut self.recursive, "Incluir subpastas")
+                    .changed()
+                {
+                    self.reload_apps();
+                }
                 ui.label(format!("{} apps", self.apps.len()));
             });
             ui.add_space(4.0);
@@ -267,6 +469,7 @@ impl eframe::App for App {
             });
 
         // Painel direito: detalhes do app selecionado
+        let mut refresh_processes = false;
         egui::SidePanel::right("details_panel")
             .min_width(320.0)
             .default_width(380.0)
@@ -274,7 +477,21 @@ impl eframe::App for App {
             .show(ctx, |ui| {
                 ui.add_space(8.0);
                 if let Some(details) = &self.selected_details {
-                    ui.heading(&details.name);
+                    ui.horizontal(|ui| {
+                        let size = egui::vec2(48.0, 48.0);
+                        match &selected_icon {
+                            Some(Some(icon)) => {
+                                ui.image((icon.id(), size));
+                            }
+                            Some(None) => {
+                                ui.allocate_exact_size(size, egui::Sense::hover());
+                            }
+                            None => {
+                                ui.add_sized(size, egui::Spinner::new());
+                            }
+                        }
+                        ui.heading(&details.name);
+                    });
                     ui.add_space(4.0);
 
                     egui::Grid::new("app_details_grid")
@@ -294,34 +511,187 @@ impl eframe::App for App {
                             ui.label(egui::RichText::new("Tamanho:").strong());
                             ui.label(format_size(details.size));
                             ui.end_row();
+
+                            if details.is_sandboxed {
+                                ui.label(egui::RichText::new("Sandbox:").strong());
+                                ui.label("sim").on_hover_text(
+                                    "Os dados do app ficam no container em ~/Library/Containers, \
+                                     que pode ser removido com seguranca.",
+                                );
+                                ui.end_row();
+                            }
+
+                            if !details.privacy.is_empty() {
+                                ui.label(egui::RichText::new("Privacidade:").strong());
+                                ui.label(details.privacy.join(", "));
+                                ui.end_row();
+                            }
+
+                            if !details.url_schemes.is_empty() {
+                                let urls: Vec<_> = details
+                                    .url_schemes
+                                    .iter()
+                                    .map(|s| s.clone() + "://")
+                                    .collect();
+                                ui.label(egui::RichText::new("URLs:").strong());
+                                ui.label(urls.join(", "));
+                                ui.end_row();
+                            }
                         });
 
-                    ui.add_space(8.0);
-                    ui.separator();
-                    ui.add_space(4.0);
+                    if let Some(id) = details.mas_app_id {
+                        if ui
+                            .link("Abrir na App Store")
+                            .on_hover_text(
+                                "Apps da App Store podem ser baixados de novo em Comprados",
+                            )
+                            .clicked()
+                        {
+                            if let Err(e) = open_mas_page(id) {
+                                self.log_messages
+                                    .push(format!("Erro ao abrir a App Store: {}", e));
+                            }
+                        }
+                    }
 
-                    if details.related.is_empty() {
-                        ui.label("Nenhum arquivo residual encontrado.");
-                    } else {
+                    if !details.document_types.is_empty() {
+                        ui.add_space(4.0);
+                        let count = details.document_types.len();
+                        egui::CollapsingHeader::new(format!("Abre tipos de arquivo ({})", count))
+                            .default_open(false)
+                            .show(ui, |ui| {
+                                for doc in &details.document_types {
+                                    let mut line = doc.name.clone();
+                                    if !doc.extensions.is_empty() {
+                                        line += &format!(" (.{})", doc.extensions.join(", ."));
+                                    }
+                                    let label = ui.label(line);
+                                    if !doc.mime_types.is_empty() {
+                                        label.on_hover_text(doc.mime_types.join(", "));
+                                    }
+                                }
+                            });
+                    }
+
+                    if !details.processes.is_empty() {
+                        ui.add_space(8.0);
                         ui.label(
                             egui::RichText::new(format!(
-                                "Arquivos residuais ({}):",
-                                details.related.len()
+                                "Processos em execucao ({}):",
+                                details.processes.len()
                             ))
                             .strong(),
                         );
+                        for (pid, path) in &details.processes {
+                            ui.monospace(format!("{:>6}  {}", pid, path.display()));
+                        }
+                        if let Some(ref bid) = details.bundle_id {
+                            if ui.button("Forcar encerramento").clicked() {
+                                force_quit_app(bid);
+                                refresh_processes = true;
+                            }
+                        }
+                    }
+
+                    ui.add_space(8.0);
+                    ui.separator();
+                    ui.add_space(4.0);
+
+                    if details.loading {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("Procurando arquivos residuais...");
+                        });
+                    } else if details.related.is_empty() {
+                        ui.label("Nenhum arquivo residual encontrado.");
+                    } else {
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "Arquivos residuais ({}):",
+                                    details.related.len()
+                                ))
+                                .strong(),
+                            );
+                            ui.selectable_value(&mut self.related_by_path, false, "Tamanho");
+                            ui.selectable_value(&mut self.related_by_path, true, "Caminho");
+                        });
+                        // O plano ja vem do maior para o menor.
+                        let mut related: Vec<_> = details.related.iter().collect();
+                        if self.related_by_path {
+                            related.sort_by(|a, b| a.path.cmp(&b.path));
+                        }
+                        let web_size: u64 = details
+                            .related
+                            .iter()
+                            .filter(|r| r.category.is_web_data())
+                            .map(|r| r.size)
+                            .sum();
+                        if web_size > 0 {
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "Dados web (WebKit/HTTPStorages): {}",
+                                    format_size(web_size)
+                                ))
+                                .strong()
+                                .color(egui::Color32::from_rgb(230, 160, 40)),
+                            );
+                        }
+                        if let Some(tmp_size) = details.tmp_size.filter(|&size| size > 0) {
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "Arquivos temporarios (Data/tmp do container): {}",
+                                    format_size(tmp_size)
+                                ))
+                                .strong(),
+                            )
+                            .on_hover_text(
+                                "Podem ser apagados periodicamente, mesmo sem remover o app.",
+                            );
+                        }
+                        if let Some(state_size) = details.saved_state_size.filter(|&s| s > 0) {
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "Estado salvo das janelas: {}",
+                                    format_size(state_size)
+                                ))
+                                .strong(),
+                            );
+                        }
                         ui.add_space(4.0);
                         egui::ScrollArea::vertical()
                             .max_height(200.0)
                             .show(ui, |ui| {
-                                for rf in &details.related {
+                                for rf in related {
                                     ui.horizontal(|ui| {
-                                        ui.monospace(format!(
-                                            "{} ({})",
+                                        let text = egui::RichText::new(format!(
+                                            "{} ({}){}",
                                             rf.path.display(),
-                                            format_size(rf.size)
-                                        ));
+                                            format_size(rf.size),
+                                            if rf.requires_elevation {
+                                                " [requer sudo]"
+                                            } else {
+                                                ""
+                                            }
+                                        ))
+                                        .monospace();
+                                        if rf.category.is_web_data() {
+                                            ui.label(text.strong());
+                                        } else {
+                                            ui.label(text);
+                                        }
                                     });
+                                    for (path, size) in &rf.contents {
+                                        ui.label(
+                                            egui::RichText::new(format!(
+                                                "    {} ({})",
+                                                path.display(),
+                                                format_size(*size)
+                                            ))
+                                            .monospace()
+                                            .weak(),
+                                        );
+                                    }
                                 }
                             });
                     }
@@ -332,30 +702,109 @@ impl eframe::App for App {
 
                     ui.label(
                         egui::RichText::new(format!(
-                            "Total a liberar: {}",
+                            "Total a liberar: {}{}",
+                            if details.loading { "~" } else { "" },
                             format_size(details.total_size)
                         ))
                         .strong()
                         .size(15.0),
                     );
 
-                    ui.add_space(12.0);
+                    if let Some(plan) = details.plan.as_ref().filter(|p| !p.warnings.is_empty()) {
+                        ui.add_space(8.0);
+                        ui.horizontal_wrapped(|ui| {
+                            for warning in &plan.warnings {
+                                warning_badge(ui, warning);
+                            }
+                        });
+                    }
 
-                    // Botao de remover
-                    let can_remove = !self.removing;
-                    ui.add_enabled_ui(can_remove, |ui| {
-                        if ui
-                            .button(
-                                egui::RichText::new("Remover aplicativo")
-                                    .size(16.0)
-                                    .color(egui::Color32::WHITE),
+                    if let Some(report) = &details.permissions {
+                        ui.add_space(8.0);
+                        if report.has_problems() {
+                            let blocked: Vec<String> = report
+                                .requires_sudo
+                                .iter()
+                                .chain(&report.read_only)
+                                .map(|p| p.display().to_string())
+                                .collect();
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "🔒 {} exigem sudo, {} somente leitura",
+                                    report.requires_sudo.len(),
+                                    report.read_only.len()
+                                ))
+                                .color(egui::Color32::from_rgb(200, 60, 60)),
                             )
-                            .clicked()
-                        {
-                            self.show_confirm = true;
+                            .on_hover_text(blocked.join("\n"));
+                        } else {
+                            ui.label(format!(
+                                "🔓 Todos os {} arquivos podem ser removidos",
+                                report.writable.len()
+                            ));
                         }
-                    });
+                    }
+
+                    let crash_logs: Vec<PathBuf> = details
+                        .related
+                        .iter()
+                        .filter(|r| r.category == RelatedFileCategory::CrashLog)
+                        .map(|r| r.path.clone())
+                        .collect();
+                    if details
+                        .related
+                        .iter()
+                        .any(|r| r.category == RelatedFileCategory::Diagnostics)
+                    {
+                        ui.add_space(8.0);
+                        ui.label(
+                            egui::RichText::new(
+                                "Arquivos de diagnostico podem conter dados sensiveis do sistema.",
+                            )
+                            .color(egui::Color32::from_rgb(230, 160, 40)),
+                        );
+                    }
+                    if !crash_logs.is_empty() {
+                        ui.add_space(8.0);
+                        ui.horizontal_wrapped(|ui| {
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "{} relatorio(s) de falha serao apagados. Ver antes de apagar?",
+                                    crash_logs.len()
+                                ))
+                                .color(egui::Color32::from_rgb(230, 160, 40)),
+                            );
+                            if ui.button("Ver relatorios de falha").clicked() {
+                                if let Err(e) = reveal_in_finder(&crash_logs) {
+                                    self.log_messages
+                                        .push(format!("Erro ao abrir o Finder: {}", e));
+                                }
+                            }
+                        });
+                    }
+
+                    ui.add_space(12.0);
 
+                    // Botao de remover
+                    let can_remove = !self.removing && !details.loading && !details.is_system;
+                    let response = ui.add_enabled(
+                        can_remove,
+                        egui::Button::new(
+                            egui::RichText::new("Remover aplicativo")
+                                .size(16.0)
+                                .color(egui::Color32::WHITE),
+                        ),
+                    );
+                    let response = if details.is_system {
+                        response.on_disabled_hover_text(
+                            "Apps de sistema sao gerenciados pelo macOS e nao podem ser removidos.",
+                        )
+                    } else {
+                        response
+                    };
+                    if response.clicked() {
+                        self.show_confirm = true;
+                    }
                 } else {
                     ui.vertical_centered(|ui| {
                         ui.add_space(80.0);
@@ -368,13 +817,33 @@ impl eframe::App for App {
                 }
             });
 
+        if refresh_processes {
+            if let Some(details) = &mut self.selected_details {
+                details.processes = details
+                    .bundle_id
+                    .as_deref()
+                    .map(get_app_processes)
+                    .unwrap_or_default();
+            }
+        }
+
         // Dialogo de confirmacao (fora do side panel para evitar conflito de borrow)
         if self.show_confirm {
-            let (confirm_name, confirm_size) = self
+            let (confirm_name, savings) = self
                 .selected_details
                 .as_ref()
-                .map(|d| (d.name.clone(), d.total_size))
+                .and_then(|d| Some((d.name.clone(), DiskSavingsEstimate::from(d.plan.as_ref()?))))
                 .unwrap_or_default();
+            let helpers = self
+                .selected_details
+                .as_ref()
+                .and_then(|d| d.plan.as_ref())
+                .and_then(|plan| {
+                    plan.warnings
+                        .iter()
+                        .find(|w| matches!(w, Warning::HelperProcessesRunning { .. }))
+                })
+                .map(|w| w.to_string());
 
             egui::Window::new("Confirmar remocao")
                 .collapsible(false)
@@ -386,9 +855,24 @@ impl eframe::App for App {
                         confirm_name
                     ));
                     ui.label(format!(
-                        "Isso ira liberar {}.",
-                        format_size(confirm_size)
+                        "Isso ira liberar {}:",
+                        format_size(savings.total_bytes)
                     ));
+                    ui.label(format!(
+                        "  aplicativo: {}",
+                        format_size(savings.total_app_bytes)
+                    ));
+                    ui.label(format!(
+                        "  arquivos residuais: {}",
+                        format_size(savings.total_residual_bytes)
+                    ));
+                    if let Some(helpers) = &helpers {
+                        ui.colored_label(egui::Color32::from_rgb(230, 160, 40), helpers);
+                    }
+                    ui.checkbox(
+                        &mut self.remove_opts.rebuild_launch_services,
+                        "Reconstruir o banco do Launch Services (leva alguns segundos)",
+                    );
                     ui.add_space(8.0);
                     ui.horizontal(|ui| {
                         if ui.button("Cancelar").clicked() {
@@ -406,6 +890,24 @@ impl eframe::App for App {
 
         // Painel central: lista de apps
         egui::CentralPanel::default().show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let user_tab = ui.selectable_label(!self.show_system, "Aplicativos");
+                let system_tab = ui.selectable_label(self.show_system, "Sistema");
+                let show_system = if system_tab.clicked() {
+                    true
+                } else if user_tab.clicked() {
+                    false
+                } else {
+                    self.show_system
+                };
+                if show_system != self.show_system {
+                    self.show_system = show_system;
+                    self.selected_index = None;
+                    self.selected_details = None;
+                }
+            });
+            ui.separator();
+
             let filtered = self.filtered_apps();
 
             if filtered.is_empty() {
@@ -421,10 +923,32 @@ impl eframe::App for App {
                     let app = &self.apps[global_idx];
                     let is_selected = self.selected_index == Some(list_pos);
 
-                    let response = ui.selectable_label(
-                        is_selected,
-                        format!("{}    {}", app.name, format_size(app.size)),
-                    );
+                    let response = ui
+                        .horizontal(|ui| {
+                            // Ponto verde para apps em execucao (transparente mantem o alinhamento)
+                            let dot_color = if app.is_running {
+                                egui::Color32::from_rgb(60, 180, 75)
+                            } else {
+                                egui::Color32::TRANSPARENT
+                            };
+                            ui.label(egui::RichText::new("●").color(dot_color));
+                            if let Some(icon) = &generic_icon {
+                                ui.image((icon.id(), egui::vec2(16.0, 16.0)));
+                            }
+                            if let Some(id) = self.duplicate_ids.get(&global_idx) {
+                                ui.label(
+                                    egui::RichText::new("⚠")
+                                        .color(egui::Color32::from_rgb(230, 160, 40)),
+                                )
+                                .on_hover_text(format!(
+                                    "Outro app usa o mesmo bundle ID ({}); os arquivos \
+                                     residuais podem ser de qualquer um dos dois.",
+                                    id
+                                ));
+                            }
+                            ui.selectable_label(is_selected, app.to_string())
+                        })
+                        .inner;
 
                     if response.clicked() {
                         self.selected_index = Some(list_pos);
@@ -435,3 +959,47 @@ impl eframe::App for App {
         });
     }
 }
+
+/// Inverte `find_duplicate_bundle_ids` para consulta por app na lista.
+fn duplicates_by_index(apps: &[AppInfo]) -> HashMap<usize, String> {
+    find_duplicate_bundle_ids(apps)
+        .into_iter()
+        .flat_map(|(id, indices)| indices.into_iter().map(move |i| (i, id.clone())))
+        .collect()
+}
+
+/// Etiqueta colorida para um aviso do plano; o texto completo aparece ao passar o mouse.
+fn warning_badge(ui: &mut egui::Ui, warning: &Warning) {
+    let (label, color) = match warning {
+        Warning::AppIsRunning => ("Em execucao", egui::Color32::from_rgb(230, 160, 40)),
+        Warning::HelperProcessesRunning { .. } => {
+            ("Processos abertos", egui::Color32::from_rgb(230, 160, 40))
+        }
+        Warning::SystemProtected => ("Sistema", egui::Color32::from_rgb(200, 60, 60)),
+        Warning::MasApp => ("App Store", egui::Color32::from_rgb(50, 120, 220)),
+        Warning::HomebrewManaged { .. } => ("Homebrew", egui::Color32::from_rgb(190, 130, 40)),
+        Warning::SystemExtensionPresent => {
+            ("Extensao de sistema", egui::Color32::from_rgb(200, 60, 60))
+        }
+        Warning::PrivilegedHelperPresent => {
+            ("Helper privilegiado", egui::Color32::from_rgb(160, 80, 200))
+        }
+        Warning::ICloudDataPresent => ("iCloud", egui::Color32::from_rgb(60, 160, 200)),
+        Warning::CloudKitDataPresent => ("CloudKit", egui::Color32::from_rgb(60, 160, 200)),
+        Warning::HstsEntries { .. } => ("HSTS", egui::Color32::from_rgb(120, 120, 120)),
+        Warning::CliToolNameMatch { .. } => ("CLI", egui::Color32::from_rgb(120, 120, 120)),
+    };
+    egui::Frame::new()
+        .fill(color)
+        .corner_radius(4.0)
+        .inner_margin(egui::Margin::symmetric(6, 2))
+        .show(ui, |ui| {
+            ui.label(
+                egui::RichText::new(label)
+                    .color(egui::Color32::WHITE)
+                    .small(),
+            );
+        })
+        .response
+        .on_hover_text(warning.to_string());
+}
@@ -0,0 +1,555 @@
+use mac_app_remover::*;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::io;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn main() -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = App::new().run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+/// Detalhes do app selecionado.
+struct SelectedDetails {
+    name: String,
+    path: PathBuf,
+    bundle_id: Option<String>,
+    is_system: bool,
+    related: Vec<RelatedFile>,
+    total_size: u64,
+    /// `total_size` ainda e a estimativa do `du`; os arquivos residuais estao sendo calculados.
+    loading: bool,
+    /// Plano de remocao, disponivel quando `loading` termina.
+    plan: Option<RemovalPlan>,
+}
+
+struct App {
+    /// Lista completa de apps (carregada uma vez, recarregada apos remover).
+    apps: Vec<AppInfo>,
+    /// Texto digitado apos `/`.
+    search_query: String,
+    /// O teclado esta editando a busca.
+    searching: bool,
+    /// Selecao na lista filtrada.
+    list_state: ListState,
+    /// Detalhes do app selecionado (carregados sob demanda).
+    selected_details: Option<SelectedDetails>,
+    /// Log de status das operacoes.
+    log_messages: Vec<String>,
+    /// Canal para receber mensagens de log da thread de remocao.
+    log_rx: Option<mpsc::Receiver<LogMsg>>,
+    /// Flag para indicar que a remocao esta em andamento.
+    removing: bool,
+    /// Opcoes usadas em `execute_plan`; `--rebuild-launch-services` liga a
+    /// reconstrucao do banco do Launch Services.
+    remove_opts: RemovalOptions,
+    /// Flag para mostrar o dialogo de confirmacao.
+    show_confirm: bool,
+    /// Canal para receber os arquivos residuais calculados em segundo plano.
+    details_rx: Option<mpsc::Receiver<DetailsMsg>>,
+    /// Quando comecar a calcular os detalhes do app selecionado; adiado a cada
+    /// tecla para nao abrir um calculo por app ao segurar as setas.
+    details_due: Option<Instant>,
+    quit: bool,
+}
+
+/// Espera sem teclas antes de calcular os detalhes do app selecionado.
+const DETAILS_DELAY: Duration = Duration::from_millis(250);
+
+/// Resultados do calculo em segundo plano para o app em `path`, nessa ordem.
+enum DetailsMsg {
+    /// Estimativa rapida do `du` (ver `estimate_removal_size`).
+    Estimate { path: PathBuf, total_size: u64 },
+    Plan {
+        path: PathBuf,
+        plan: Option<RemovalPlan>,
+    },
+}
+
+enum LogMsg {
+    Line(String),
+    Done,
+}
+
+impl App {
+    fn new() -> Self {
+        let mut app = Self {
+            apps: get_installed_app_infos(),
+            search_query: String::new(),
+            searching: false,
+            list_state: ListState::default(),
+            selected_details: None,
+            log_messages: Vec::new(),
+            log_rx: None,
+            removing: false,
+            remove_opts: RemovalOptions {
+                rebuild_launch_services: std::env::args().any(|a| a == "--rebuild-launch-services"),
+                ..Default::default()
+            },
+            show_confirm: false,
+            details_rx: None,
+            details_due: None,
+            quit: false,
+        };
+        app.select(0);
+        app
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        while !self.quit {
+            self.poll_log();
+            self.start_details();
+            self.poll_details();
+            terminal.draw(|frame| self.draw(frame))?;
+            // Timeout curto para atualizar log e detalhes vindos das threads.
+            if event::poll(Duration::from_millis(100))? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key.code, key.modifiers);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn filtered_apps(&self) -> Vec<usize> {
+        let q = self.search_query.to_lowercase();
+        self.apps
+            .iter()
+            .enumerate()
+            .filter(|(_, app)| q.is_empty() || app.name.to_lowercase().contains(&q))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Seleciona a posicao `pos` da lista filtrada e carrega os detalhes.
+    fn select(&mut self, pos: usize) {
+        let filtered = self.filtered_apps();
+        if filtered.is_empty() {
+            self.list_state.select(None);
+            self.selected_details = None;
+            return;
+        }
+        let pos = pos.min(filtered.len() - 1);
+        self.list_state.select(Some(pos));
+        let app = &self.apps[filtered[pos]];
+        if self
+            .selected_details
+            .as_ref()
+            .is_some_and(|d| d.path == app.path)
+        {
+            return;
+        }
+
+        // Descarta o calculo do app anterior; o novo so comeca em `start_details`.
+        self.details_rx = None;
+        self.details_due = Some(Instant::now() + DETAILS_DELAY);
+
+        self.selected_details = Some(SelectedDetails {
+            name: app.name.clone(),
+            path: app.path.clone(),
+            bundle_id: app.bundle_id.clone(),
+            is_system: app.is_system,
+            related: Vec::new(),
+            total_size: app.size,
+            loading: true,
+            plan: None,
+        });
+    }
+
+    /// Calcula os detalhes do app selecionado quando `details_due` passa.
+    fn start_details(&mut self) {
+        if self.details_due.is_none_or(|due| Instant::now() < due) {
+            return;
+        }
+        self.details_due = None;
+        let Some(details) = &self.selected_details else {
+            return;
+        };
+
+        // `du` e dir_size dos residuais podem demorar: calcula fora da thread da UI.
+        let (tx, rx) = mpsc::channel();
+        let path = details.path.clone();
+        thread::spawn(move || {
+            let estimate = DetailsMsg::Estimate {
+                path: path.clone(),
+                total_size: estimate_removal_size(&path),
+            };
+            // Outro app foi selecionado enquanto o `du` rodava.
+            if tx.send(estimate).is_err() {
+                return;
+            }
+            let plan = plan_removal_at(&path, &RemovalOptions::default(), &Config::default()).ok();
+            let _ = tx.send(DetailsMsg::Plan { path, plan });
+        });
+        self.details_rx = Some(rx);
+    }
+
+    fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        if code == KeyCode::Char('c') && modifiers.contains(KeyModifiers::CONTROL) {
+            self.quit = true;
+            return;
+        }
+
+        if self.show_confirm {
+            match code {
+                KeyCode::Char('y') | KeyCode::Char('s') => self.start_removal(),
+                KeyCode::Char('n') | KeyCode::Esc => self.show_confirm = false,
+                _ => {}
+            }
+            return;
+        }
+
+        if self.searching {
+            match code {
+                KeyCode::Char(c) => self.search_query.push(c),
+                KeyCode::Backspace => {
+                    self.search_query.pop();
+                }
+                KeyCode::Enter | KeyCode::Esc => self.searching = false,
+                _ => return,
+            }
+            self.select(0);
+            return;
+        }
+
+        let pos = self.list_state.selected().unwrap_or(0);
+        match code {
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Char('/') => self.searching = true,
+            KeyCode::Up => self.select(pos.saturating_sub(1)),
+            KeyCode::Down => self.select(pos + 1),
+            KeyCode::PageUp => self.select(pos.saturating_sub(10)),
+            KeyCode::PageDown => self.select(pos + 10),
+            KeyCode::Char('d') => {
+                let can_remove = self
+                    .selected_details
+                    .as_ref()
+                    .is_some_and(|d| d.plan.is_some() && !d.is_system);
+                if can_remove && !self.removing {
+                    self.show_confirm = true;
+                } else if self.selected_details.as_ref().is_some_and(|d| d.is_system) {
+                    self.log_messages
+                        .push("Apps de sistema nao podem ser removidos.".to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn poll_details(&mut self) {
+        loop {
+            let msg = match self.details_rx.as_ref().map(|rx| rx.try_recv()) {
+                Some(Ok(msg)) => msg,
+                Some(Err(mpsc::TryRecvError::Empty)) | None => return,
+                Some(Err(mpsc::TryRecvError::Disconnected)) => {
+                    self.details_rx = None;
+                    return;
+                }
+            };
+            let Some(details) = &mut self.selected_details else {
+                continue;
+            };
+            match msg {
+                // Ignora resultados de um app que ja nao esta selecionado.
+                DetailsMsg::Estimate { path, total_size } if details.path == path => {
+                    details.total_size = details.total_size.max(total_size);
+                }
+                DetailsMsg::Plan { path, plan } if details.path == path => {
+                    self.details_rx = None;
+                    if let Some(plan) = &plan {
+                        details.total_size = plan.total_bytes;
+                        details.related = plan.related.clone();
+                    }
+                    details.plan = plan;
+                    details.loading = false;
+                    return;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn start_removal(&mut self) {
+        let (plan, app_name) = match &self.selected_details {
+            Some(SelectedDetails {
+                plan: Some(plan),
+                name,
+                ..
+            }) => (plan.clone(), name.clone()),
+            _ => return,
+        };
+
+        let opts = self.remove_opts.clone();
+        let (tx, rx) = mpsc::channel();
+        self.log_rx = Some(rx);
+        self.removing = true;
+        self.log_messages.clear();
+        self.show_confirm = false;
+
+        thread::spawn(move || {
+            let log = |line: String| {
+                let _ = tx.send(LogMsg::Line(line));
+            };
+            // Log sem as linhas "Removendo ...": o painel e pequeno.
+            let progress = |p: RemovalProgress| {
+                if !matches!(p, RemovalProgress::Removing { .. }) {
+                    log(p.to_string().trim_start().to_string());
+                }
+            };
+            match execute_plan(&plan, &opts, progress) {
+                Ok(result) => {
+                    for note in &result.notes {
+                        log(format!("Aviso: {}", note));
+                    }
+                    if !result.removed.is_empty() {
+                        let record =
+                            RemovalRecord::new(&result, plan.bundle_id.clone(), opts.strategy);
+                        if let Err(e) = append_removal_record(record) {
+                            log(format!("Aviso: historico nao foi salvo: {}", e));
+                        }
+                    }
+                    if result.is_success() {
+                        log(format!("\"{}\" removido com sucesso!", app_name));
+                    } else {
+                        log(format!(
+                            "\"{}\" removido com {} erro(s).",
+                            app_name,
+                            result.errors.len()
+                        ));
+                    }
+                }
+                Err(e) => log(format!("{}.", e)),
+            }
+            let _ = tx.send(LogMsg::Done);
+        });
+    }
+
+    fn poll_log(&mut self) {
+        let mut done = false;
+        if let Some(rx) = &self.log_rx {
+            while let Ok(msg) = rx.try_recv() {
+                match msg {
+                    LogMsg::Line(line) => self.log_messages.push(line),
+                    LogMsg::Done => done = true,
+                }
+            }
+        }
+
+        if done {
+            self.removing = false;
+            self.log_rx = None;
+            self.apps = get_installed_app_infos();
+            self.selected_details = None;
+            self.select(self.list_state.selected().unwrap_or(0));
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, log_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(8)]).areas(frame.area());
+        let [list_area, details_area] =
+            Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)])
+                .areas(main);
+
+        self.draw_list(frame, list_area);
+        self.draw_details(frame, details_area);
+
+        let log_lines: Vec<Line> = self
+            .log_messages
+            .iter()
+            .rev()
+            .take(log_area.height.saturating_sub(2) as usize)
+            .rev()
+            .map(|l| Line::from(l.as_str()))
+            .collect();
+        frame.render_widget(
+            Paragraph::new(log_lines).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Log ")
+                    .title_bottom(" ↑↓ navegar  / buscar  d remover  q sair "),
+            ),
+            log_area,
+        );
+
+        if self.show_confirm {
+            self.draw_confirm(frame);
+        }
+    }
+
+    fn draw_list(&mut self, frame: &mut Frame, area: Rect) {
+        let filtered = self.filtered_apps();
+        let items: Vec<ListItem> = filtered
+            .iter()
+            .map(|&i| {
+                let app = &self.apps[i];
+                let mut spans = vec![Span::raw(app.name.clone())];
+                if app.is_system {
+                    spans.push(Span::styled(
+                        " [sistema]",
+                        Style::default().fg(Color::DarkGray),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let search = if self.searching {
+            format!(" /{}_ ", self.search_query)
+        } else if self.search_query.is_empty() {
+            String::new()
+        } else {
+            format!(" /{} ", self.search_query)
+        };
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" Aplicativos ({}) ", filtered.len()))
+                    .title_bottom(search),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, area, &mut self.list_state);
+    }
+
+    fn draw_details(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::default().borders(Borders::ALL).title(" Detalhes ");
+        let details = match &self.selected_details {
+            Some(d) => d,
+            None => {
+                frame.render_widget(
+                    Paragraph::new("Nenhum aplicativo selecionado.").block(block),
+                    area,
+                );
+                return;
+            }
+        };
+
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let mut lines = vec![
+            Line::styled(details.name.clone(), bold),
+            Line::from(format!("Caminho:   {}", details.path.display())),
+            Line::from(format!(
+                "Bundle ID: {}",
+                details.bundle_id.as_deref().unwrap_or("-")
+            )),
+            Line::from(format!(
+                "Total:     {}{}",
+                if details.loading { "~" } else { "" },
+                format_size(details.total_size)
+            )),
+            Line::default(),
+        ];
+        if let Some(plan) = details.plan.as_ref().filter(|p| !p.warnings.is_empty()) {
+            for warning in &plan.warnings {
+                lines.push(Line::styled(
+                    format!("! {}", warning),
+                    Style::default().fg(Color::Yellow),
+                ));
+            }
+            lines.push(Line::default());
+        }
+        if details.is_system {
+            lines.push(Line::styled(
+                "App de sistema: nao pode ser removido.",
+                Style::default().fg(Color::Yellow),
+            ));
+        } else if details.loading {
+            lines.push(Line::from("Procurando arquivos residuais..."));
+        } else if details.related.is_empty() {
+            lines.push(Line::from("Nenhum arquivo residual encontrado."));
+        } else {
+            lines.push(Line::styled(
+                format!("Arquivos residuais ({}):", details.related.len()),
+                bold,
+            ));
+            for file in &details.related {
+                let mut spans = vec![
+                    Span::raw(format!("{:>10}  ", format_size(file.size))),
+                    Span::styled(
+                        file.path.display().to_string(),
+                        Style::default().fg(Color::Cyan),
+                    ),
+                    Span::raw(format!(" [{}]", file.category.label())),
+                ];
+                if file.requires_elevation {
+                    spans.push(Span::styled(
+                        " [requer sudo]",
+                        Style::default().fg(Color::Yellow),
+                    ));
+                }
+                lines.push(Line::from(spans));
+                for (path, size) in &file.contents {
+                    lines.push(Line::styled(
+                        format!("{:>10}    {}", format_size(*size), path.display()),
+                        Style::default().fg(Color::DarkGray),
+                    ));
+                }
+            }
+        }
+
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .wrap(Wrap { trim: false }),
+            area,
+        );
+    }
+
+    fn draw_confirm(&self, frame: &mut Frame) {
+        let name = self
+            .selected_details
+            .as_ref()
+            .map(|d| d.name.as_str())
+            .unwrap_or_default();
+        let helpers = self
+            .selected_details
+            .as_ref()
+            .and_then(|d| d.plan.as_ref())
+            .and_then(|plan| {
+                plan.warnings.iter().find_map(|w| match w {
+                    Warning::HelperProcessesRunning { processes } => Some(processes.len()),
+                    _ => None,
+                })
+            });
+        let mut lines = vec![Line::from(format!(
+            "Remover \"{}\" e seus arquivos residuais?",
+            name
+        ))];
+        if let Some(count) = helpers {
+            lines.push(Line::styled(
+                format!("{} processo(s) do app serao encerrados.", count),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        lines.push(Line::default());
+        lines.push(Line::from("y = remover   n = cancelar"));
+        let height = lines.len() as u16 + 2;
+        let area = frame.area();
+        let width = 50.min(area.width);
+        let popup = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + area.height.saturating_sub(height) / 2,
+            width,
+            height.min(area.height),
+        );
+        frame.render_widget(Clear, popup);
+        frame.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: true })
+                .block(Block::default().borders(Borders::ALL).title(" Confirmar ")),
+            popup,
+        );
+    }
+}
@@ -1,8 +1,55 @@
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::env;
+use std::fmt;
 use std::fs;
-use std::io;
+use std::io::{self, BufRead, Read, Write};
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+
+/// Emite `log::<nivel>!` quando a feature `log` esta ativa; sem ela, nao gera
+/// codigo (os argumentos nem sao avaliados).
+macro_rules! log_at {
+    ($level:ident, $($arg:tt)*) => {
+        #[cfg(feature = "log")]
+        log::$level!($($arg)*);
+    };
+}
+
+/// API publica pretendida: os tipos principais e as funcoes para achar,
+/// planejar e remover apps e formatar tamanhos.
+///
+/// ```no_run
+/// use mac_app_remover::prelude::*;
+///
+/// if let Some(app) = find_app("Safari") {
+///     let bundle_id = get_bundle_id(&app);
+///     for file in find_related_files("Safari", bundle_id.as_deref()) {
+///         println!("{} ({})", file.path.display(), format_size(file.size));
+///     }
+/// }
+/// ```
+///
+/// O restante do crate (ex: `dir_size_strict`, `find_mail_plugins`,
+/// `clear_window_restoration`) continua publico, mas e considerado auxiliar.
+pub mod prelude {
+    pub use crate::{
+        batch_remove_apps, estimate_disk_savings, execute_plan, filter_related_files, find_app,
+        find_related_files, find_related_files_with_config, format_size, get_bundle_id,
+        get_installed_app_infos, get_installed_apps, parse_size_string, plan_removal,
+        plan_removal_at, search_apps_with_mode,
+    };
+    pub use crate::{
+        AppInfo, AppVersion, BatchProgress, Config, DiskSavingsEstimate, RelatedFile,
+        RelatedFileCategory, RemovalOptions, RemovalPlan, RemovalProgress, RemovalResult,
+        RemovalStrategy, RemoverError, SearchMatchMode, Warning,
+    };
+}
 
 /// Informacoes sobre um aplicativo instalado.
 pub struct AppInfo {
@@ -10,26 +57,750 @@ pub struct AppInfo {
     pub path: PathBuf,
     pub size: u64,
     pub bundle_id: Option<String>,
+    /// Preenchido apenas por `get_installed_app_infos_opts(true, _)`.
+    pub is_running: bool,
+    /// App do macOS em /System (nao deve ser removido).
+    pub is_system: bool,
+    /// Data de criacao do bundle (aproximadamente a data de instalacao).
+    pub installed_date: Option<SystemTime>,
+    /// Versao como aparece no Info.plist (ex: `2.10.3`).
+    pub version: Option<String>,
+    /// `version` convertida para comparacao numerica.
+    pub version_parsed: Option<AppVersion>,
+    /// Chaves `NS*UsageDescription` do Info.plist (ver `get_privacy_keys`).
+    pub privacy_keys: Vec<String>,
+    /// `NSHumanReadableCopyright` do Info.plist.
+    pub copyright: Option<String>,
 }
 
-/// Retorna todos os diretórios .app de /Applications e ~/Applications.
-pub fn get_installed_apps() -> Vec<PathBuf> {
-    let mut apps = Vec::new();
-    let dirs = vec![
-        PathBuf::from("/Applications"),
-        get_home().join("Applications"),
-    ];
+/// Dois `AppInfo` sao iguais quando apontam para o mesmo bundle. O bundle ID
+/// vem do Info.plist do proprio caminho, entao o caminho basta: apps com o
+/// mesmo bundle ID em lugares diferentes continuam distintos.
+impl PartialEq for AppInfo {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
 
-    for dir in dirs {
-        if let Ok(entries) = fs::read_dir(&dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.extension().and_then(|e| e.to_str()) == Some("app") {
-                    apps.push(path);
-                }
+impl Eq for AppInfo {}
+
+impl std::hash::Hash for AppInfo {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.path.hash(state);
+    }
+}
+
+/// `Nome (42.3 MB)`.
+impl fmt::Display for AppInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name, format_size(self.size))
+    }
+}
+
+impl AppInfo {
+    /// Recursos protegidos que o app pode pedir, em nomes legiveis e sem repeticao.
+    pub fn privacy_permissions(&self) -> Vec<String> {
+        privacy_resource_names(&self.privacy_keys)
+    }
+
+    /// Tem o entitlement `com.apple.security.app-sandbox` (ver
+    /// `is_app_sandboxed`). Roda o `codesign` a cada chamada, por isso nao e
+    /// lido ao listar os apps.
+    pub fn is_sandboxed(&self) -> bool {
+        is_app_sandboxed(&self.path)
+    }
+}
+
+/// Versao `major.minor.patch` com sufixo opcional (ex: `1.2.0-beta2`, `3.1b4`).
+/// Compara numericamente, e uma pre-release vem antes da versao final.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    pub pre: Option<String>,
+}
+
+impl AppVersion {
+    /// Componentes ausentes viram 0 (`"2.1"` = 2.1.0) e um quarto componente
+    /// numerico e ignorado. Retorna `None` se nao comecar com um numero.
+    pub fn parse(s: &str) -> Option<AppVersion> {
+        let s = s.trim();
+        let s = s.strip_prefix(['v', 'V']).unwrap_or(s);
+        let end = s
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(s.len());
+        let (numbers, rest) = s.split_at(end);
+        let mut parts = numbers.split('.');
+        let major = parts.next()?.parse().ok()?;
+        let mut next = || parts.next().and_then(|p| p.parse().ok()).unwrap_or(0);
+        let (minor, patch) = (next(), next());
+        let pre = rest.trim_start_matches(['-', '+', ' ', '.']).trim();
+        Some(AppVersion {
+            major,
+            minor,
+            patch,
+            pre: (!pre.is_empty()).then(|| pre.to_string()),
+        })
+    }
+}
+
+impl Ord for AppVersion {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (&self.pre, &other.pre) {
+                (None, None) => std::cmp::Ordering::Equal,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (Some(a), Some(b)) => a.cmp(b),
+            })
+    }
+}
+
+impl PartialOrd for AppVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for AppVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre) = &self.pre {
+            write!(f, "-{}", pre)?;
+        }
+        Ok(())
+    }
+}
+
+/// Ordena da versao mais antiga para a mais nova; apps sem versao vao para o fim.
+pub fn sort_apps_by_version(apps: &mut [AppInfo]) {
+    apps.sort_by(|a, b| match (&a.version_parsed, &b.version_parsed) {
+        (Some(x), Some(y)) => x.cmp(y),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+}
+
+/// Tipo de arquivo residual, definido pelo diretorio onde foi encontrado.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelatedFileCategory {
+    AppSupport,
+    Cache,
+    Preferences,
+    Logs,
+    Container,
+    GroupContainer,
+    SavedState,
+    WebData,
+    /// Dados web achados pelo nome do app, quando nenhum diretorio tem o bundle ID.
+    WebDataByName,
+    Cookies,
+    MailPlugin,
+    ContactsPlugin,
+    Biome,
+    NetworkData,
+    Spelling,
+    CrashLog,
+    Diagnostics,
+    RecentItems,
+    ExtensionContainer,
+    Developer,
+    TemporaryFiles,
+    CloudKit,
+    /// Dados do antigo Sync Services (MobileMe/iCloud, ~2012-2016).
+    SyncServices,
+    /// Atualizacoes baixadas pelo Sparkle (ver `find_sparkle_cache`).
+    UpdateCache,
+    /// Ferramenta de linha de comando instalada pelo app (ver `find_installed_cli_tools`).
+    HelperTool,
+}
+
+impl RelatedFileCategory {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RelatedFileCategory::AppSupport => "Application Support",
+            RelatedFileCategory::Cache => "Cache",
+            RelatedFileCategory::Preferences => "Preferencias",
+            RelatedFileCategory::Logs => "Logs",
+            RelatedFileCategory::Container => "Container",
+            RelatedFileCategory::GroupContainer => "Group Container",
+            RelatedFileCategory::SavedState => "Estado salvo",
+            RelatedFileCategory::WebData => "Dados web",
+            RelatedFileCategory::WebDataByName => "Dados web (pelo nome do app)",
+            RelatedFileCategory::Cookies => "Cookies",
+            RelatedFileCategory::MailPlugin => "Plugin do Mail",
+            RelatedFileCategory::ContactsPlugin => "Plugin do Contatos",
+            RelatedFileCategory::Biome => "Dados de uso (Biome)",
+            RelatedFileCategory::NetworkData => "Downloads em segundo plano",
+            RelatedFileCategory::Spelling => "Dicionario ortografico",
+            RelatedFileCategory::CrashLog => "Relatorio de falha",
+            RelatedFileCategory::Diagnostics => "Diagnostico",
+            RelatedFileCategory::RecentItems => "Itens recentes",
+            RelatedFileCategory::ExtensionContainer => "Container de extensao",
+            RelatedFileCategory::Developer => "Desenvolvimento",
+            RelatedFileCategory::TemporaryFiles => "Arquivos temporarios",
+            RelatedFileCategory::CloudKit => "Dados do CloudKit",
+            RelatedFileCategory::SyncServices => "Sync Services",
+            RelatedFileCategory::UpdateCache => "Atualizacoes baixadas",
+            RelatedFileCategory::HelperTool => "Ferramenta de linha de comando",
+        }
+    }
+
+    /// Continua como entrada propria mesmo dentro de outra entrada encontrada
+    /// (ex: o CloudKit dentro do container), para mostrar o proprio tamanho e
+    /// a propria nota.
+    pub fn listed_inside_parent(&self) -> bool {
+        matches!(
+            self,
+            RelatedFileCategory::CloudKit
+                | RelatedFileCategory::UpdateCache
+                | RelatedFileCategory::CrashLog
+        )
+    }
+
+    /// Dados do WebKit/HTTPStorages, achados por qualquer criterio.
+    pub fn is_web_data(&self) -> bool {
+        matches!(
+            self,
+            RelatedFileCategory::WebData | RelatedFileCategory::WebDataByName
+        )
+    }
+
+    /// Aviso a mostrar depois que arquivos desta categoria forem removidos.
+    pub fn removal_note(&self) -> Option<&'static str> {
+        match self {
+            RelatedFileCategory::NetworkData => Some(
+                "downloads em segundo plano do app (nsurlsessiond) podem continuar \
+                 ate a proxima reinicializacao, sem como cancela-los.",
+            ),
+            // O NSSpellChecker nao pode ser reiniciado por um app comum.
+            RelatedFileCategory::Spelling => Some(
+                "o corretor ortografico pode manter o dicionario removido em memoria; \
+                 reinicie o Mac (ou encerre sessao) para recarrega-lo.",
+            ),
+            RelatedFileCategory::CloudKit => Some(
+                "a copia local dos dados do app sincronizados pelo iCloud (CloudKit) foi \
+                 apagada; os dados continuam na conta iCloud.",
+            ),
+            RelatedFileCategory::SyncServices => Some(
+                "dados do Sync Services (MobileMe/iCloud antigo, de 2012-2016) foram \
+                 apagados; o macOS atual nao os usa mais.",
+            ),
+            RelatedFileCategory::GroupContainer => Some(
+                "Group Containers podem ser compartilhados com outros apps do mesmo \
+                 desenvolvedor, que perdem os dados guardados ali.",
+            ),
+            RelatedFileCategory::Developer => Some(
+                "caches do Xcode (DerivedData, simuladores, suporte a dispositivos) costumam \
+                 ocupar dezenas de GB e sao recriados se o Xcode for instalado de novo.",
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// Avisos (sem repeticao) das categorias dos arquivos removidos.
+pub fn removal_notes<'a>(files: impl IntoIterator<Item = &'a RelatedFile>) -> Vec<&'static str> {
+    let mut notes = Vec::new();
+    for note in files.into_iter().filter_map(|f| f.category.removal_note()) {
+        if !notes.contains(&note) {
+            notes.push(note);
+        }
+    }
+    notes
+}
+
+/// Arquivo residual encontrado por `find_related_files`.
+#[derive(Debug, Clone)]
+pub struct RelatedFile {
+    pub path: PathBuf,
+    pub category: RelatedFileCategory,
+    pub size: u64,
+    /// Arquivo fora do home do usuario, exige `sudo` para remover.
+    pub requires_elevation: bool,
+    /// Subpastas ou arquivos de destaque dentro de `path` (ex: o `GPUCache` do
+    /// Chromium), com o tamanho de cada um. Sao removidos junto com `path`;
+    /// so mostram para onde vai o espaco.
+    pub contents: Vec<(PathBuf, u64)>,
+}
+
+/// Dados do atributo `com.apple.quarantine` de um app baixado.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QuarantineInfo {
+    pub origin_url: Option<String>,
+    pub timestamp: Option<SystemTime>,
+    /// App que baixou o arquivo (ex: "Safari" ou "com.google.Chrome").
+    pub agent_bundle_id: Option<String>,
+}
+
+/// Criterio para decidir se uma entrada de diretorio pertence ao app.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchMode {
+    /// O nome contem o nome do app ou o bundle ID (sem diferenciar maiusculas).
+    SubstringAny,
+    /// O nome e exatamente o bundle ID.
+    ExactBundleId,
+    /// O nome comeca com o bundle ID (ex: `com.example.app.helper`).
+    PrefixBundleId,
+}
+
+impl MatchMode {
+    pub fn matches(&self, entry_name: &str, app_name: &str, bundle_id: Option<&str>) -> bool {
+        let name = entry_name.to_lowercase();
+        match self {
+            MatchMode::SubstringAny => std::iter::once(app_name).chain(bundle_id).any(|term| {
+                log_at!(
+                    trace,
+                    "comparando \"{}\" com o termo \"{}\"",
+                    entry_name,
+                    term
+                );
+                name.contains(&term.to_lowercase())
+            }),
+            MatchMode::ExactBundleId => bundle_id.is_some_and(|id| name == id.to_lowercase()),
+            MatchMode::PrefixBundleId => {
+                bundle_id.is_some_and(|id| name.starts_with(&id.to_lowercase()))
+            }
+        }
+    }
+}
+
+/// Um diretorio varrido por `find_related_files`.
+#[derive(Debug, Clone)]
+pub struct SearchSpec {
+    pub dir: PathBuf,
+    /// Niveis abaixo de `dir` a examinar (1 = apenas entradas diretas).
+    pub depth: usize,
+    pub match_mode: MatchMode,
+    /// Categoria atribuida as entradas encontradas.
+    pub category: RelatedFileCategory,
+    /// Busca usada so quando a principal nao encontra nada.
+    pub fallback: Option<(MatchMode, RelatedFileCategory)>,
+}
+
+impl SearchSpec {
+    pub fn new(
+        dir: PathBuf,
+        depth: usize,
+        match_mode: MatchMode,
+        category: RelatedFileCategory,
+    ) -> Self {
+        SearchSpec {
+            dir,
+            depth,
+            match_mode,
+            category,
+            fallback: None,
+        }
+    }
+
+    /// Se `match_mode` nao encontrar nada, varre de novo com `mode`.
+    pub fn with_fallback(mut self, mode: MatchMode, category: RelatedFileCategory) -> Self {
+        self.fallback = Some((mode, category));
+        self
+    }
+}
+
+/// Configuracao da busca por arquivos residuais.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    /// Inclui /Library (Application Support, Caches, Logs, Preferences) na busca.
+    /// Desativado por padrao: esses arquivos exigem `sudo` e podem ser compartilhados.
+    pub search_system_library: bool,
+    /// Informa cada diretorio varrido e cada arquivo encontrado: via `log::info!`
+    /// com a feature `log`, senao direto no stderr.
+    pub verbose: bool,
+    /// Team ID do desenvolvedor (ver `get_app_team_id`). Quando informado, Group
+    /// Containers `<team-id>.*` tambem entram na busca.
+    pub team_id: Option<String>,
+    /// Tambem pergunta ao Spotlight por arquivos em ~/Library/Caches criados
+    /// pelo app (ver `find_caches_by_creator`). Mais lento, mas acha caches com
+    /// nomes que nao lembram o app.
+    pub thorough: bool,
+}
+
+impl Config {
+    fn report(&self, msg: fmt::Arguments) {
+        if !self.verbose {
+            return;
+        }
+        #[cfg(feature = "log")]
+        log::info!("{}", msg);
+        #[cfg(not(feature = "log"))]
+        eprintln!("{}", msg);
+    }
+}
+
+/// Opcoes que controlam o que e removido junto com o app.
+#[derive(Debug, Clone)]
+pub struct RemovalOptions {
+    /// Mantem os arquivos de preferencias (para uma reinstalacao futura).
+    pub keep_preferences: bool,
+    /// Mantem os caches.
+    pub keep_caches: bool,
+    /// Tentativas de `remove_path_with_retry` para arquivos ocupados (ex: abertos no Finder).
+    pub retry_attempts: usize,
+    /// Espera entre as tentativas.
+    pub retry_delay: Duration,
+    /// Como cada arquivo e removido.
+    pub strategy: RemovalStrategy,
+    /// Liga `Config::search_system_library` no plano: inclui /Library na busca.
+    pub include_system_library: bool,
+    /// Reconstroi o banco do Launch Services (`lsregister -kill -r`) se algum
+    /// handler foi tirado das preferencias. Leva alguns segundos; em
+    /// `batch_remove_apps` roda uma vez so, no fim.
+    pub rebuild_launch_services: bool,
+}
+
+impl Default for RemovalOptions {
+    fn default() -> Self {
+        Self {
+            keep_preferences: false,
+            keep_caches: false,
+            retry_attempts: 3,
+            retry_delay: Duration::from_millis(500),
+            strategy: RemovalStrategy::default(),
+            include_system_library: false,
+            rebuild_launch_services: false,
+        }
+    }
+}
+
+/// Como `remove_path_safely` se livra de um arquivo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RemovalStrategy {
+    /// Apaga de vez (`remove_dir_all` / `remove_file`).
+    #[default]
+    PermanentDelete,
+    /// Move para `~/.Trash`, de onde o usuario ainda pode recuperar.
+    MoveToTrash,
+}
+
+impl RemovalOptions {
+    /// Indica se o arquivo deve entrar na lista de remocao.
+    pub fn should_remove(&self, file: &RelatedFile) -> bool {
+        match file.category {
+            RelatedFileCategory::Preferences => !self.keep_preferences,
+            RelatedFileCategory::Cache | RelatedFileCategory::UpdateCache => !self.keep_caches,
+            _ => true,
+        }
+    }
+}
+
+/// Erros das operacoes de remocao.
+#[derive(Debug)]
+pub enum RemoverError {
+    /// Nenhum aplicativo encontrado com o nome informado.
+    AppNotFound(String),
+    /// App de sistema (/System), gerenciado pelo macOS.
+    SystemApp(PathBuf),
+    /// Operacao cancelada pelo usuario.
+    Cancelled,
+    /// Tamanho em texto que `parse_size_string` nao entendeu.
+    InvalidSize(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for RemoverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemoverError::AppNotFound(name) => {
+                write!(f, "Aplicativo \"{}\" nao encontrado", name)
+            }
+            RemoverError::SystemApp(path) => write!(
+                f,
+                "{} e um app de sistema e nao pode ser removido",
+                path.display()
+            ),
+            RemoverError::Cancelled => write!(f, "Operacao cancelada"),
+            RemoverError::InvalidSize(text) => {
+                write!(f, "Tamanho invalido: \"{}\" (use ex: 500MB, 1.5GB)", text)
+            }
+            RemoverError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for RemoverError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RemoverError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for RemoverError {
+    fn from(e: io::Error) -> Self {
+        RemoverError::Io(e)
+    }
+}
+
+/// Resultado de uma remocao: o que foi removido, pulado e o que falhou.
+#[derive(Debug, Clone, Default)]
+pub struct RemovalResult {
+    pub app_path: PathBuf,
+    pub removed: Vec<PathBuf>,
+    pub skipped: Vec<PathBuf>,
+    pub errors: Vec<(PathBuf, String)>,
+    /// Avisos para o usuario sobre o que foi removido.
+    pub notes: Vec<String>,
+    /// `(caminho original, caminho na Lixeira)` do que foi movido com `MoveToTrash`.
+    pub trashed: Vec<(PathBuf, PathBuf)>,
+    /// Handlers tirados das preferencias do Launch Services (ver `unregister_launch_services`).
+    pub launch_services_handlers: usize,
+}
+
+impl RemovalResult {
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// O que `execute_plan` vai remover, calculado por `plan_removal` sem alterar nada.
+#[derive(Debug, Clone)]
+pub struct RemovalPlan {
+    pub app_path: PathBuf,
+    pub bundle_id: Option<String>,
+    pub app_size: u64,
+    /// Arquivos residuais, ja filtrados pelas `RemovalOptions`.
+    pub related: Vec<RelatedFile>,
+    /// Tamanho do app mais o dos arquivos residuais.
+    pub total_bytes: u64,
+    pub warnings: Vec<Warning>,
+}
+
+/// Aviso nao fatal sobre um plano de remocao.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// O app esta aberto; `execute_plan` vai fecha-lo antes de remover.
+    AppIsRunning,
+    /// Processos com o bundle ID (helpers XPC, agentes) estao abertos;
+    /// `execute_plan` vai encerra-los (ver `force_quit_app`).
+    HelperProcessesRunning { processes: Vec<PathBuf> },
+    /// O app fica em /System e e protegido pelo SIP.
+    SystemProtected,
+    /// Instalado pela Mac App Store (tem `_MASReceipt`).
+    MasApp,
+    /// Instalado como cask do Homebrew; `brew` continuara achando que esta instalado.
+    HomebrewManaged { cask: String },
+    /// O bundle traz extensoes de sistema (`Contents/Library/SystemExtensions`).
+    SystemExtensionPresent,
+    /// O app instala um helper privilegiado (SMJobBless) fora do bundle.
+    PrivilegedHelperPresent,
+    /// Ha documentos do app no iCloud Drive, que nao sao removidos.
+    ICloudDataPresent,
+    /// O container tem dados sincronizados pelo CloudKit (ver `find_cloudkit_data`).
+    CloudKitDataPresent,
+    /// O HSTS.plist tem hosts do dominio do app (ver `find_hsts_entries`).
+    HstsEntries { hosts: Vec<String> },
+    /// Executaveis com o nome do app que nao apontam para o bundle (ver
+    /// `find_cli_tools_by_name`); nao sao removidos.
+    CliToolNameMatch { paths: Vec<PathBuf> },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::AppIsRunning => {
+                write!(f, "O app esta em execucao e sera fechado antes da remocao")
+            }
+            Warning::HelperProcessesRunning { processes } => {
+                let paths: Vec<String> =
+                    processes.iter().map(|p| p.display().to_string()).collect();
+                write!(f, "Processos do app serao encerrados: {}", paths.join(", "))
+            }
+            Warning::SystemProtected => {
+                write!(
+                    f,
+                    "App de sistema protegido pelo macOS; nao pode ser removido"
+                )
+            }
+            Warning::MasApp => write!(
+                f,
+                "Instalado pela App Store; pode ser reinstalado pela aba Comprados"
+            ),
+            Warning::HomebrewManaged { cask } => write!(
+                f,
+                "Gerenciado pelo Homebrew; prefira `brew uninstall --cask {}`",
+                cask
+            ),
+            Warning::SystemExtensionPresent => write!(
+                f,
+                "Contem extensoes de sistema; desative-as em Ajustes do Sistema antes de remover"
+            ),
+            Warning::PrivilegedHelperPresent => write!(
+                f,
+                "Instala um helper privilegiado em /Library; remove-lo exige sudo"
+            ),
+            Warning::ICloudDataPresent => {
+                write!(f, "Tem documentos no iCloud Drive, que serao mantidos")
+            }
+            Warning::CloudKitDataPresent => write!(
+                f,
+                "Guarda dados sincronizados pelo iCloud (CloudKit); a copia neste Mac sera apagada"
+            ),
+            Warning::HstsEntries { hosts } => write!(
+                f,
+                "~/Library/Cookies/HSTS.plist tem entradas de {}; o arquivo e compartilhado \
+                 e nao sera alterado",
+                hosts.join(", ")
+            ),
+            Warning::CliToolNameMatch { paths } => {
+                let paths: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+                write!(
+                    f,
+                    "{} tem o nome do app, mas nao aponta para ele; confira e remova a mao \
+                     se for dele",
+                    paths.join(", ")
+                )
+            }
+        }
+    }
+}
+
+/// Eventos emitidos por `execute_plan`, na ordem em que acontecem.
+#[derive(Debug, Clone)]
+pub enum RemovalProgress {
+    /// O app estava aberto e foi pedido para fechar.
+    QuittingApp,
+    /// Processos auxiliares (XPC, agentes) continuaram abertos e foram encerrados.
+    ForceQuitting {
+        processes: usize,
+    },
+    /// Antes de remover `path`; `index` comeca em 0 e `total` inclui o app.
+    Removing {
+        path: PathBuf,
+        index: usize,
+        total: usize,
+    },
+    Removed {
+        path: PathBuf,
+    },
+    Failed {
+        path: PathBuf,
+        error: String,
+    },
+    /// Mail ou Contatos sendo recarregado por causa de um plugin removido.
+    ReloadingPlugins {
+        host: &'static str,
+    },
+    /// Reconstruindo o banco do Launch Services (`RemovalOptions::rebuild_launch_services`).
+    RebuildingLaunchServices,
+}
+
+impl fmt::Display for RemovalProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemovalProgress::QuittingApp => write!(f, "App em execucao, tentando fechar..."),
+            RemovalProgress::ForceQuitting { processes } => {
+                write!(f, "Encerrando {} processo(s) restante(s)...", processes)
+            }
+            RemovalProgress::Removing { path, .. } => {
+                write!(f, "Removendo {}...", path.display())
+            }
+            RemovalProgress::Removed { path } => write!(f, "  {} - OK", path.display()),
+            RemovalProgress::Failed { path, error } => {
+                write!(f, "  {} - ERRO: {}", path.display(), error)
+            }
+            RemovalProgress::ReloadingPlugins { host } => {
+                write!(f, "Recarregando plugins do {}...", host)
+            }
+            RemovalProgress::RebuildingLaunchServices => {
+                write!(f, "Reconstruindo o banco do Launch Services...")
+            }
+        }
+    }
+}
+
+/// Resposta do usuario para um arquivo residual no modo seguro.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SafeModeChoice {
+    Remove,
+    Skip,
+    Quit,
+}
+
+/// Origem das confirmacoes do modo seguro (stdin na CLI, mock nos testes).
+pub trait SafeModePrompt {
+    /// Confirma a remocao do bundle `.app`.
+    fn confirm_app(&mut self, app_path: &Path, size: u64) -> bool;
+    /// Pergunta o que fazer com um arquivo residual.
+    fn ask_file(&mut self, path: &Path, size: u64) -> SafeModeChoice;
+}
+
+/// Implementacao padrao de `SafeModePrompt` lendo respostas do stdin.
+pub struct StdinPrompt;
+
+impl StdinPrompt {
+    /// `None` no fim da entrada (ou erro de leitura), para nao perguntar para sempre.
+    fn read_answer(input: &mut impl BufRead) -> Option<String> {
+        io::stdout().flush().ok();
+        let mut line = String::new();
+        match input.read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(line.trim().to_lowercase()),
+        }
+    }
+
+    /// Pergunta ate receber uma resposta valida; no fim da entrada, `Quit`.
+    fn file_choice(input: &mut impl BufRead) -> SafeModeChoice {
+        loop {
+            print!("  (r)emove, (s)kip, (q)uit: ");
+            let Some(answer) = Self::read_answer(input) else {
+                return SafeModeChoice::Quit;
+            };
+            match answer.as_str() {
+                "r" | "remove" => return SafeModeChoice::Remove,
+                "s" | "skip" => return SafeModeChoice::Skip,
+                "q" | "quit" => return SafeModeChoice::Quit,
+                _ => continue,
             }
         }
     }
+}
+
+impl SafeModePrompt for StdinPrompt {
+    fn confirm_app(&mut self, app_path: &Path, size: u64) -> bool {
+        print!(
+            "Remover {} ({})? (s/N): ",
+            app_path.display(),
+            format_size(size)
+        );
+        matches!(
+            Self::read_answer(&mut io::stdin().lock()).as_deref(),
+            Some("s" | "sim" | "y" | "yes")
+        )
+    }
+
+    fn ask_file(&mut self, path: &Path, size: u64) -> SafeModeChoice {
+        println!("  {} ({})", path.display(), format_size(size));
+        Self::file_choice(&mut io::stdin().lock())
+    }
+}
+
+/// Retorna todos os diretórios .app de /Applications, ~/Applications e /System/Applications.
+pub fn get_installed_apps() -> Vec<PathBuf> {
+    get_installed_apps_opts(false)
+}
+
+/// Igual a `get_installed_apps`; com `recursive` tambem procura em subpastas
+/// como /Applications/Utilities ou /Applications/Adobe (ate 2 niveis).
+pub fn get_installed_apps_opts(recursive: bool) -> Vec<PathBuf> {
+    get_installed_apps_in(&app_dirs(), recursive)
+}
+
+/// Igual a `get_installed_apps_opts`, mas procura apenas nos diretorios informados.
+pub fn get_installed_apps_in(dirs: &[PathBuf], recursive: bool) -> Vec<PathBuf> {
+    let mut apps = Vec::new();
+    let max_depth = if recursive { 2 } else { 1 };
+    for dir in dirs {
+        collect_app_bundles(dir, max_depth, &mut apps);
+    }
+    dedup_app_bundles(&mut apps);
 
     apps.sort_by(|a, b| {
         a.file_stem()
@@ -40,9 +811,61 @@ pub fn get_installed_apps() -> Vec<PathBuf> {
     apps
 }
 
+fn app_dirs() -> Vec<PathBuf> {
+    vec![
+        PathBuf::from("/Applications"),
+        get_home().join("Applications"),
+        PathBuf::from("/System/Applications"),
+    ]
+}
+
+/// Apps em /System sao gerenciados pelo macOS (protegidos pelo SIP).
+pub fn is_system_app(app_path: &Path) -> bool {
+    app_path.starts_with("/System")
+}
+
+/// Remove bundles que apontam para o mesmo app (ex: symlinks do Homebrew Cask em
+/// /Applications), mantendo o caminho do symlink para exibicao.
+fn dedup_app_bundles(apps: &mut Vec<PathBuf>) {
+    apps.sort_by_key(|path| !is_symlink(path));
+    let mut seen = HashSet::new();
+    apps.retain(|path| seen.insert(fs::canonicalize(path).unwrap_or_else(|_| path.clone())));
+}
+
+fn is_symlink(path: &Path) -> bool {
+    fs::symlink_metadata(path)
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false)
+}
+
+/// Coleta bundles .app em `dir`, descendo em subpastas (mas nunca dentro de um .app).
+fn collect_app_bundles(dir: &Path, max_depth: usize, apps: &mut Vec<PathBuf>) {
+    if max_depth == 0 {
+        return;
+    }
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) == Some("app") {
+                apps.push(path);
+            } else if path.is_dir() {
+                collect_app_bundles(&path, max_depth - 1, apps);
+            }
+        }
+    }
+}
+
 /// Retorna informacoes detalhadas de todos os apps instalados.
 pub fn get_installed_app_infos() -> Vec<AppInfo> {
-    get_installed_apps()
+    get_installed_app_infos_opts(false, false)
+}
+
+/// Igual a `get_installed_app_infos`; com `with_running_state` tambem verifica
+/// quais apps estao em execucao (um `pgrep` por app), e `recursive` e repassado
+/// para `get_installed_apps_opts`.
+pub fn get_installed_app_infos_opts(with_running_state: bool, recursive: bool) -> Vec<AppInfo> {
+    let indexed_ids = get_all_bundle_ids_fast();
+    get_installed_apps_opts(recursive)
         .into_iter()
         .map(|path| {
             let name = path
@@ -51,187 +874,6383 @@ pub fn get_installed_app_infos() -> Vec<AppInfo> {
                 .to_string_lossy()
                 .to_string();
             let size = dir_size(&path).unwrap_or(0);
-            let bundle_id = get_bundle_id(&path);
+            let plist = get_app_info_extended(&path).unwrap_or_default();
+            // Spotlight primeiro; apps fora do indice caem no Info.plist
+            let bundle_id = indexed_ids
+                .get(&path)
+                .cloned()
+                .or_else(|| plist.bundle_id.clone());
+            let is_running = with_running_state && is_app_running(&name);
+            let is_system = is_system_app(&path);
+            let installed_date = get_installed_date(&path);
+            let version = plist.version().map(str::to_string);
+            let version_parsed = version.as_deref().and_then(AppVersion::parse);
+            let privacy_keys = plist.privacy_keys;
+            let copyright = plist.copyright;
             AppInfo {
                 name,
                 path,
                 size,
                 bundle_id,
+                is_running,
+                is_system,
+                installed_date,
+                version,
+                version_parsed,
+                privacy_keys,
+                copyright,
             }
         })
         .collect()
 }
 
-pub fn find_app(name: &str) -> Option<PathBuf> {
-    let search_dirs = vec![
-        PathBuf::from("/Applications"),
-        get_home().join("Applications"),
-    ];
-
-    let app_filename = if name.ends_with(".app") {
-        name.to_string()
-    } else {
-        format!("{}.app", name)
-    };
+/// Apps cujo bundle foi criado depois de `threshold`.
+pub fn find_apps_installed_after(threshold: SystemTime) -> Vec<AppInfo> {
+    get_installed_app_infos()
+        .into_iter()
+        .filter(|app| app.installed_date.is_some_and(|date| date > threshold))
+        .collect()
+}
 
-    // Busca exata
-    for dir in &search_dirs {
-        let path = dir.join(&app_filename);
-        if path.exists() {
-            return Some(path);
+/// Bundle IDs usados por mais de um app em `apps`, com os indices desses apps.
+/// Acontece com copias modificadas de um app e confunde a busca por residuais.
+pub fn find_duplicate_bundle_ids(apps: &[AppInfo]) -> HashMap<String, Vec<usize>> {
+    let mut by_id: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, app) in apps.iter().enumerate() {
+        if let Some(id) = &app.bundle_id {
+            by_id.entry(id.clone()).or_default().push(i);
         }
     }
+    by_id.retain(|_, indices| indices.len() > 1);
+    by_id
+}
 
-    // Busca case-insensitive
-    let name_lower = app_filename.to_lowercase();
-    for dir in &search_dirs {
-        if let Ok(entries) = fs::read_dir(dir) {
-            for entry in entries.flatten() {
-                let entry_name = entry.file_name().to_string_lossy().to_lowercase();
-                if entry_name == name_lower {
-                    return Some(entry.path());
-                }
-            }
-        }
-    }
+/// Apps cujo `LSMinimumSystemVersion` e mais novo que o macOS atual.
+pub fn find_incompatible_apps() -> Vec<AppInfo> {
+    let Some(current) = get_current_macos_version() else {
+        return Vec::new();
+    };
+    get_installed_app_infos()
+        .into_iter()
+        .filter(|app| !is_compatible_with(&app.path, current))
+        .collect()
+}
 
-    None
+/// Apps com pelo menos `threshold` bytes, do maior para o menor.
+pub fn find_large_apps(threshold: u64) -> Vec<AppInfo> {
+    let mut apps: Vec<AppInfo> = get_installed_app_infos()
+        .into_iter()
+        .filter(|app| app.size >= threshold)
+        .collect();
+    apps.sort_by_key(|app| std::cmp::Reverse(app.size));
+    apps
 }
 
-pub fn get_bundle_id(app_path: &Path) -> Option<String> {
-    let plist = app_path.join("Contents/Info.plist");
-    if !plist.exists() {
-        return None;
+/// Converte tamanhos como `500MB`, `1.5GB` ou `2 g` em bytes. Usa as mesmas
+/// unidades de `format_size` (base 1024); sem unidade, o valor e em bytes.
+pub fn parse_size_string(s: &str) -> Result<u64, RemoverError> {
+    let invalid = || RemoverError::InvalidSize(s.to_string());
+    let text = s.trim();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let value: f64 = number.replace(',', ".").parse().map_err(|_| invalid())?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" => 1 << 10,
+        "M" | "MB" => 1 << 20,
+        "G" | "GB" => 1 << 30,
+        "T" | "TB" => 1 << 40,
+        _ => return Err(invalid()),
+    };
+    let bytes = value * multiplier as f64;
+    if !bytes.is_finite() || bytes >= u64::MAX as f64 {
+        return Err(invalid());
     }
+    Ok(bytes.round() as u64)
+}
 
-    let output = Command::new("defaults")
-        .args(["read", &plist.to_string_lossy(), "CFBundleIdentifier"])
+/// Data de criacao do bundle. Se o sistema de arquivos nao informar,
+/// usa `kMDItemContentCreationDate` do Spotlight.
+pub fn get_installed_date(app_path: &Path) -> Option<SystemTime> {
+    if let Ok(created) = fs::metadata(app_path).and_then(|m| m.created()) {
+        return Some(created);
+    }
+    let output = Command::new("mdls")
+        .args([
+            "-raw",
+            "-name",
+            "kMDItemContentCreationDate",
+            &app_path.to_string_lossy(),
+        ])
         .output()
         .ok()?;
+    parse_mdls_date(&String::from_utf8_lossy(&output.stdout))
+}
 
-    if output.status.success() {
-        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
-    } else {
-        None
+/// Converte `AAAA-MM-DD` (meia-noite UTC) em `SystemTime`.
+pub fn parse_date(text: &str) -> Option<SystemTime> {
+    let mut parts = text.trim().splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: u32 = parts.next()?.parse().ok()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+        return None;
     }
+    let secs = days_from_civil(year, month, day) * 86_400;
+    let secs = u64::try_from(secs).ok()?;
+    Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
 }
 
-pub fn find_related_files(app_name: &str, bundle_id: Option<&str>) -> Vec<PathBuf> {
-    let home = get_home();
-    let mut found = Vec::new();
+/// Formato do `mdls`: `2024-01-15 10:20:30 +0000`. `(null)` vira `None`.
+fn parse_mdls_date(text: &str) -> Option<SystemTime> {
+    let mut fields = text.split_whitespace();
+    let date = parse_date(fields.next()?)?;
+    let mut hms = fields.next()?.splitn(3, ':');
+    let mut secs = 0u64;
+    for unit in [3600, 60, 1] {
+        secs += hms.next()?.parse::<u64>().ok()? * unit;
+    }
+    let date = date + Duration::from_secs(secs);
 
-    let search_dirs: Vec<PathBuf> = vec![
-        home.join("Library/Application Support"),
-        home.join("Library/Caches"),
-        home.join("Library/Preferences"),
-        home.join("Library/Logs"),
-        home.join("Library/Containers"),
-        home.join("Library/Group Containers"),
-        home.join("Library/Saved Application State"),
-        home.join("Library/WebKit"),
-        home.join("Library/HTTPStorages"),
-        home.join("Library/Cookies"),
-    ];
+    let offset = fields.next().unwrap_or("+0000");
+    let (sign, digits) = offset.split_at(1);
+    let hours: u64 = digits.get(..2)?.parse().ok()?;
+    let minutes: u64 = digits.get(2..4)?.parse().ok()?;
+    let offset = Duration::from_secs(hours * 3600 + minutes * 60);
+    match sign {
+        "+" => date.checked_sub(offset),
+        "-" => date.checked_add(offset),
+        _ => None,
+    }
+}
 
-    let mut search_terms: Vec<String> = vec![app_name.to_string()];
-    if let Some(id) = bundle_id {
-        search_terms.push(id.to_string());
+/// 28 a 31, contando os anos bissextos do calendario gregoriano.
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
     }
+}
 
-    for dir in &search_dirs {
-        if !dir.exists() {
-            continue;
+/// Dias desde 1970-01-01 (algoritmo `days_from_civil` de Howard Hinnant).
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let month = i64::from(month);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Nome usado para comparar apps: sem espacos nas pontas, sem `.app`, em
+/// minusculas e na forma Unicode NFC (o Finder grava nomes em NFD).
+pub fn normalize_app_name(name: &str) -> String {
+    let normalized: String = name.to_lowercase().nfc().collect();
+    let mut trimmed = normalized.as_str();
+    loop {
+        let next = trimmed.trim();
+        let next = next.strip_suffix(".app").unwrap_or(next);
+        if next == trimmed {
+            return next.to_string();
         }
-        if let Ok(entries) = fs::read_dir(dir) {
-            for entry in entries.flatten() {
-                let entry_name = entry.file_name().to_string_lossy().to_string();
-                for term in &search_terms {
-                    if entry_name == *term
-                        || entry_name.to_lowercase() == term.to_lowercase()
-                        || entry_name.contains(term)
-                        || entry_name
-                            .to_lowercase()
-                            .contains(&term.to_lowercase())
-                    {
-                        found.push(entry.path());
-                        break;
-                    }
-                }
+        trimmed = next;
+    }
+}
+
+/// Como `search_apps_with_mode` compara o nome do app com a busca.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchMatchMode {
+    /// O nome contem a busca.
+    #[default]
+    Substring,
+    /// O nome e a busca (sem diferenciar maiusculas).
+    Exact,
+    /// As letras da busca aparecem no nome nessa ordem (ex: `gchr` em `Google Chrome`).
+    Fuzzy,
+}
+
+impl SearchMatchMode {
+    /// Compara depois de `normalize_app_name`, entao maiusculas, `.app` e a
+    /// forma Unicode nao fazem diferenca.
+    pub fn matches(&self, name: &str, query: &str) -> bool {
+        let name = normalize_app_name(name);
+        let query = normalize_app_name(query);
+        match self {
+            SearchMatchMode::Substring => name.contains(&query),
+            SearchMatchMode::Exact => name == query,
+            SearchMatchMode::Fuzzy => {
+                let mut letters = name.chars();
+                query
+                    .chars()
+                    .filter(|c| !c.is_whitespace())
+                    .all(|q| letters.any(|c| c == q))
             }
         }
     }
+}
 
-    if let Some(id) = bundle_id {
-        let pref_dir = home.join("Library/Preferences");
-        let plist_file = pref_dir.join(format!("{}.plist", id));
-        if plist_file.exists() && !found.contains(&plist_file) {
-            found.push(plist_file);
+/// Apps instalados cujo nome, ou nome localizado (`get_localized_name`), casa
+/// com `query` segundo `mode`.
+pub fn search_apps_with_mode(query: &str, mode: SearchMatchMode, recursive: bool) -> Vec<PathBuf> {
+    search_apps_in(&app_dirs(), query, mode, recursive)
+}
+
+fn search_apps_in(
+    dirs: &[PathBuf],
+    query: &str,
+    mode: SearchMatchMode,
+    recursive: bool,
+) -> Vec<PathBuf> {
+    get_installed_apps_in(dirs, recursive)
+        .into_iter()
+        .filter(|app| {
+            mode.matches(
+                &app.file_stem().unwrap_or_default().to_string_lossy(),
+                query,
+            ) || get_localized_name(app).is_some_and(|name| mode.matches(&name, query))
+        })
+        .collect()
+}
+
+pub fn find_app(name: &str) -> Option<PathBuf> {
+    find_app_in(&app_dirs(), name)
+}
+
+/// Igual a `find_app`, mas procura apenas nos diretorios informados.
+pub fn find_app_in(search_dirs: &[PathBuf], name: &str) -> Option<PathBuf> {
+    let app_filename = if name.ends_with(".app") {
+        name.to_string()
+    } else {
+        format!("{}.app", name)
+    };
+
+    // Busca exata
+    for dir in search_dirs {
+        let path = dir.join(&app_filename);
+        if path.exists() {
+            return Some(path);
         }
     }
 
-    found.sort();
-    found.dedup();
-    found
+    // Busca ignorando maiusculas e a forma de normalizacao Unicode
+    let wanted = normalize_app_name(name);
+    let is_match = |file_name: &std::ffi::OsStr| {
+        let file_name = file_name.to_string_lossy();
+        file_name.to_lowercase().ends_with(".app") && normalize_app_name(&file_name) == wanted
+    };
+    for dir in search_dirs {
+        if let Ok(entries) = fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                if is_match(&entry.file_name()) {
+                    return Some(entry.path());
+                }
+            }
+        }
+    }
+
+    // Busca em subpastas (ex: /Applications/Utilities)
+    let mut nested = Vec::new();
+    for dir in search_dirs {
+        collect_app_bundles(dir, 2, &mut nested);
+    }
+    nested
+        .into_iter()
+        .find(|path| path.file_name().is_some_and(is_match))
 }
 
-pub fn is_app_running(app_name: &str) -> bool {
-    let output = Command::new("pgrep")
-        .args(["-f", &format!("{}.app", app_name)])
-        .output();
+/// Bundle IDs de todos os apps indexados pelo Spotlight, em uma unica chamada ao `mdfind`.
+/// Apps fora do indice (ou com o Spotlight desativado) nao aparecem no mapa.
+pub fn get_all_bundle_ids_fast() -> HashMap<PathBuf, String> {
+    let mut cmd = Command::new("mdfind");
+    cmd.args(["-name", "*.app"]);
+    for dir in app_dirs() {
+        cmd.arg("-onlyin").arg(dir);
+    }
+    cmd.args(["-attr", "kMDItemCFBundleIdentifier"]);
 
-    matches!(output, Ok(o) if o.status.success())
+    match cmd.output() {
+        Ok(o) if o.status.success() => parse_mdfind_bundle_ids(&String::from_utf8_lossy(&o.stdout)),
+        _ => HashMap::new(),
+    }
 }
 
-pub fn quit_app(app_name: &str) {
-    let _ = Command::new("osascript")
-        .args([
-            "-e",
-            &format!("tell application \"{}\" to quit", app_name),
-        ])
+/// Arquivos em ~/Library/Caches cujo `kMDItemContentCreator` (gravado pelo
+/// Spotlight) e o app. Sem o Spotlight, retorna uma lista vazia.
+pub fn find_caches_by_creator(app_name: &str) -> Vec<PathBuf> {
+    find_caches_by_creator_in(&get_home(), app_name)
+}
+
+fn find_caches_by_creator_in(home: &Path, app_name: &str) -> Vec<PathBuf> {
+    let caches = home.join("Library/Caches");
+    let output = Command::new("mdfind")
+        .arg(content_creator_query(app_name))
+        .arg("-onlyin")
+        .arg(&caches)
         .output();
+    match output {
+        Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout)
+            .lines()
+            .map(PathBuf::from)
+            .filter(|p| p.starts_with(&caches) && p.exists())
+            .collect(),
+        _ => Vec::new(),
+    }
 }
 
-pub fn remove_path(path: &Path) -> io::Result<()> {
-    if path.is_dir() {
-        fs::remove_dir_all(path)
-    } else {
-        fs::remove_file(path)
+/// `kMDItemContentCreator == '<app_name>'`, com aspas e barras escapadas.
+fn content_creator_query(app_name: &str) -> String {
+    let escaped = app_name.replace('\\', "\\\\").replace('\'', "\\'");
+    format!("kMDItemContentCreator == '{}'", escaped)
+}
+
+/// Interpreta linhas `<caminho>   kMDItemCFBundleIdentifier = <id>`.
+fn parse_mdfind_bundle_ids(output: &str) -> HashMap<PathBuf, String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (path, id) = line.rsplit_once("kMDItemCFBundleIdentifier = ")?;
+            let id = id.trim();
+            if id.is_empty() || id == "(null)" {
+                return None;
+            }
+            Some((PathBuf::from(path.trim_end()), id.to_string()))
+        })
+        .collect()
+}
+
+/// Chaves do Info.plist usadas pelo programa, lidas de uma vez.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExtendedAppInfo {
+    pub bundle_id: Option<String>,
+    /// `CFBundleShortVersionString`.
+    pub short_version: Option<String>,
+    /// `CFBundleVersion` (numero do build).
+    pub bundle_version: Option<String>,
+    pub executable: Option<String>,
+    pub display_name: Option<String>,
+    pub minimum_system_version: Option<String>,
+    /// `LSApplicationCategoryType` (ex: `public.app-category.productivity`).
+    pub category_type: Option<String>,
+    pub icon_file: Option<String>,
+    /// Chaves `NS*UsageDescription` (ver `get_privacy_keys`).
+    pub privacy_keys: Vec<String>,
+    /// `NSHumanReadableCopyright`.
+    pub copyright: Option<String>,
+}
+
+impl ExtendedAppInfo {
+    /// Igual a `get_app_version`.
+    pub fn version(&self) -> Option<&str> {
+        self.short_version
+            .as_deref()
+            .or(self.bundle_version.as_deref())
     }
 }
 
-pub fn dir_size(path: &Path) -> io::Result<u64> {
-    let mut total: u64 = 0;
-    if path.is_file() {
-        return Ok(fs::metadata(path)?.len());
+/// Le o Info.plist uma unica vez e extrai todas as chaves de `ExtendedAppInfo`.
+pub fn get_app_info_extended(app_path: &Path) -> Result<ExtendedAppInfo, RemoverError> {
+    let plist = parse_info_plist(app_path).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} nao encontrado", get_info_plist_path(app_path).display()),
+        )
+    })?;
+    let value = |key| plist.string(key);
+    Ok(ExtendedAppInfo {
+        bundle_id: value("CFBundleIdentifier"),
+        short_version: value("CFBundleShortVersionString"),
+        bundle_version: value("CFBundleVersion"),
+        executable: value("CFBundleExecutable"),
+        display_name: value("CFBundleDisplayName"),
+        minimum_system_version: value("LSMinimumSystemVersion"),
+        category_type: value("LSApplicationCategoryType"),
+        icon_file: value("CFBundleIconFile"),
+        privacy_keys: privacy_keys_in(plist.keys()),
+        copyright: value("NSHumanReadableCopyright"),
+    })
+}
+
+pub fn get_bundle_id(app_path: &Path) -> Option<String> {
+    read_info_plist_string(app_path, "CFBundleIdentifier")
+}
+
+/// Nome que o Finder mostra (`CFBundleDisplayName`), quando difere do nome do
+/// bundle (ex: `Code.app` aparece como "Visual Studio Code").
+pub fn get_localized_name(app_path: &Path) -> Option<String> {
+    read_info_plist_string(app_path, "CFBundleDisplayName").filter(|name| !name.is_empty())
+}
+
+/// Versao exibida pelo app (`CFBundleShortVersionString`, ou `CFBundleVersion`).
+pub fn get_app_version(app_path: &Path) -> Option<String> {
+    read_info_plist_string(app_path, "CFBundleShortVersionString")
+        .or_else(|| read_info_plist_string(app_path, "CFBundleVersion"))
+}
+
+/// Esquemas de URL registrados pelo app (`CFBundleURLSchemes` de cada item de
+/// `CFBundleURLTypes`), ex: `slack`. Sem repeticao, na ordem do Info.plist.
+pub fn get_app_url_schemes(app_path: &Path) -> Vec<String> {
+    parse_info_plist(app_path)
+        .and_then(|plist| plist.value("CFBundleURLTypes"))
+        .map(|types| url_schemes_in(&types))
+        .unwrap_or_default()
+}
+
+fn url_schemes_in(url_types: &serde_json::Value) -> Vec<String> {
+    let mut schemes: Vec<String> = Vec::new();
+    let items = url_types.as_array().into_iter().flatten();
+    for scheme in items.flat_map(|t| json_strings(&t["CFBundleURLSchemes"])) {
+        if !schemes.contains(&scheme) {
+            schemes.push(scheme);
+        }
     }
-    for entry in fs::read_dir(path)? {
-        let entry = entry?;
-        let meta = entry.metadata()?;
-        if meta.is_dir() {
-            total += dir_size(&entry.path()).unwrap_or(0);
-        } else {
-            total += meta.len();
+    schemes
+}
+
+/// Tipo de arquivo que o app declara abrir (item de `CFBundleDocumentTypes`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentType {
+    /// `CFBundleTypeName`, ou o primeiro UTI de `LSItemContentTypes`.
+    pub name: String,
+    /// `CFBundleTypeExtensions`, sem o ponto (`*` = qualquer arquivo).
+    pub extensions: Vec<String>,
+    /// `CFBundleTypeMIMETypes`.
+    pub mime_types: Vec<String>,
+}
+
+/// Tipos de arquivo declarados em `CFBundleDocumentTypes`. Depois de remover o
+/// app, o Launch Services continua associando esses tipos a ele ate o
+/// `lsregister` rodar (ver `unregister_app_handlers`).
+pub fn get_app_document_types(app_path: &Path) -> Vec<DocumentType> {
+    parse_info_plist(app_path)
+        .and_then(|plist| plist.value("CFBundleDocumentTypes"))
+        .map(|types| document_types_in(&types))
+        .unwrap_or_default()
+}
+
+fn document_types_in(document_types: &serde_json::Value) -> Vec<DocumentType> {
+    let items = document_types.as_array().into_iter().flatten();
+    items
+        .filter_map(|t| {
+            let name = t["CFBundleTypeName"]
+                .as_str()
+                .map(str::to_string)
+                .or_else(|| json_strings(&t["LSItemContentTypes"]).into_iter().next())?;
+            Some(DocumentType {
+                name,
+                extensions: json_strings(&t["CFBundleTypeExtensions"]),
+                mime_types: json_strings(&t["CFBundleTypeMIMETypes"]),
+            })
+        })
+        .collect()
+}
+
+/// Strings de um array JSON; outros valores sao ignorados.
+fn json_strings(value: &serde_json::Value) -> Vec<String> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Texto de copyright do app (`NSHumanReadableCopyright`).
+pub fn get_app_copyright(app_path: &Path) -> Option<String> {
+    read_info_plist_string(app_path, "NSHumanReadableCopyright")
+}
+
+/// `LSMinimumSystemVersion` do Info.plist como `(maior, menor, correcao)`.
+pub fn get_app_minimum_system_version(app_path: &Path) -> Option<(u32, u32, u32)> {
+    parse_os_version(&read_info_plist_string(app_path, "LSMinimumSystemVersion")?)
+}
+
+/// Versao do macOS em execucao, lida de `sw_vers -productVersion`.
+pub fn get_current_macos_version() -> Option<(u32, u32, u32)> {
+    let output = Command::new("sw_vers")
+        .arg("-productVersion")
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_os_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_os_version(s: &str) -> Option<(u32, u32, u32)> {
+    let v = AppVersion::parse(s)?;
+    Some((v.major, v.minor, v.patch))
+}
+
+/// O app roda neste macOS. Sem `LSMinimumSystemVersion` (ou sem conseguir ler
+/// a versao do sistema) o app e considerado compativel.
+pub fn is_app_compatible(app_path: &Path) -> bool {
+    get_current_macos_version().is_none_or(|current| is_compatible_with(app_path, current))
+}
+
+fn is_compatible_with(app_path: &Path, current: (u32, u32, u32)) -> bool {
+    get_app_minimum_system_version(app_path).is_none_or(|minimum| minimum <= current)
+}
+
+/// `get_app_version` ja convertida para comparacao.
+pub fn get_app_version_from_plist(app_path: &Path) -> Option<AppVersion> {
+    AppVersion::parse(&get_app_version(app_path)?)
+}
+
+/// Nomes das chaves `NS*UsageDescription` do Info.plist, que indicam os recursos
+/// protegidos que o app pode pedir (camera, microfone...). O texto de cada chave
+/// nao e lido.
+pub fn get_privacy_keys(app_path: &Path) -> Vec<String> {
+    parse_info_plist(app_path)
+        .map(|plist| privacy_keys_in(plist.keys()))
+        .unwrap_or_default()
+}
+
+fn privacy_keys_in<'a>(keys: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut keys: Vec<String> = keys
+        .into_iter()
+        .filter(|key| key.starts_with("NS") && key.ends_with("UsageDescription"))
+        .map(str::to_string)
+        .collect();
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
+/// Entitlements da assinatura do app, como plist XML
+/// (`codesign --display --entitlements :-`). `None` se o app nao tem nenhum.
+pub fn get_app_entitlements(app_path: &Path) -> Option<String> {
+    let output = Command::new("codesign")
+        .args(["--display", "--entitlements", ":-"])
+        .arg(app_path)
+        .output()
+        .ok()?;
+    let xml = String::from_utf8_lossy(&output.stdout);
+    (output.status.success() && xml.contains("<plist")).then(|| xml.into_owned())
+}
+
+/// O app roda no App Sandbox. Os dados de apps assim ficam todos no proprio
+/// container em ~/Library/Containers, que pode ser removido sem risco.
+pub fn is_app_sandboxed(app_path: &Path) -> bool {
+    get_app_entitlements(app_path)
+        .is_some_and(|xml| entitlement_enabled(&xml, "com.apple.security.app-sandbox"))
+}
+
+/// A chave existe e vale `<true/>`.
+fn entitlement_enabled(xml: &str, key: &str) -> bool {
+    let tag = format!("<key>{}</key>", key);
+    xml.find(&tag)
+        .is_some_and(|at| xml[at + tag.len()..].trim_start().starts_with("<true/>"))
+}
+
+/// Nome legivel do recurso de uma chave de `get_privacy_keys`
+/// (ex: `NSCameraUsageDescription` -> "Camera").
+pub fn privacy_resource_name(key: &str) -> String {
+    let resource = key
+        .strip_prefix("NS")
+        .and_then(|k| k.strip_suffix("UsageDescription"))
+        .unwrap_or(key);
+    let name = match resource {
+        "Camera" => "Camera",
+        "Microphone" => "Microfone",
+        "Location" | "LocationWhenInUse" | "LocationAlways" | "LocationAlwaysAndWhenInUse" => {
+            "Localizacao"
+        }
+        "Contacts" => "Contatos",
+        "Calendars" => "Calendarios",
+        "Reminders" => "Lembretes",
+        "PhotoLibrary" | "PhotoLibraryAdd" => "Fotos",
+        "AppleEvents" => "Automacao (Apple Events)",
+        "Bluetooth" | "BluetoothAlways" | "BluetoothPeripheral" => "Bluetooth",
+        "DesktopFolder" => "Pasta Mesa",
+        "DocumentsFolder" => "Pasta Documentos",
+        "DownloadsFolder" => "Pasta Downloads",
+        "RemovableVolumes" => "Volumes removiveis",
+        "NetworkVolumes" => "Volumes de rede",
+        "SpeechRecognition" => "Reconhecimento de fala",
+        "SystemAdministration" => "Administracao do sistema",
+        "LocalNetwork" => "Rede local",
+        "HomeKit" => "Casa",
+        "AppleMusic" => "Biblioteca de musicas",
+        "Motion" => "Movimento",
+        "FocusStatus" => "Status de foco",
+        other => return other.to_string(),
+    };
+    name.to_string()
+}
+
+/// `privacy_resource_name` de cada chave, sem repetir (varias chaves de localizacao
+/// viram uma so).
+pub fn privacy_resource_names(keys: &[String]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for name in keys.iter().map(|k| privacy_resource_name(k)) {
+        if !names.contains(&name) {
+            names.push(name);
         }
     }
-    Ok(total)
+    names
 }
 
-pub fn format_size(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
+/// `<app>/Contents/Info.plist`.
+pub fn get_info_plist_path(app_path: &Path) -> PathBuf {
+    app_path.join("Contents/Info.plist")
+}
 
-    if bytes >= GB {
-        format!("{:.1} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.1} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.1} KB", bytes as f64 / KB as f64)
+/// Icone do app em RGBA como `(largura, altura, pixels)`, com `size` pixels no
+/// maior lado. Usa o `.icns` de `CFBundleIconFile`; apps que so tem o icone no
+/// catalogo de assets (`Assets.car`) retornam `None`.
+pub fn load_app_icon_rgba(app_path: &Path, size: u32) -> Option<(u32, u32, Vec<u8>)> {
+    let icon = get_app_info_extended(app_path).ok()?.icon_file?;
+    let mut icns = app_path.join("Contents/Resources").join(icon);
+    if icns.extension().is_none() {
+        icns.set_extension("icns");
+    }
+    load_icns_rgba(&icns, size)
+}
+
+/// Converte um `.icns` com o `sips` e decodifica o PNG gerado.
+pub fn load_icns_rgba(icns: &Path, size: u32) -> Option<(u32, u32, Vec<u8>)> {
+    static NEXT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+    let n = NEXT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let name = format!("mac-app-remover-icon-{}-{}.png", std::process::id(), n);
+    let png = env::temp_dir().join(name);
+    let status = Command::new("sips")
+        .args(["-s", "format", "png", "-Z", &size.to_string()])
+        .arg(icns)
+        .arg("--out")
+        .arg(&png)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    let bytes = fs::read(&png);
+    let _ = fs::remove_file(&png);
+    if !status.ok()?.success() {
+        return None;
+    }
+    let image = image::load_from_memory(&bytes.ok()?).ok()?.to_rgba8();
+    Some((image.width(), image.height(), image.into_raw()))
+}
+
+/// Executavel principal (`Contents/MacOS/<CFBundleExecutable>`). Sem a chave,
+/// usa o nome do bundle, que e o padrao do Xcode. Nao confere se o arquivo existe.
+pub fn get_main_executable_path(app_path: &Path) -> Option<PathBuf> {
+    let executable = read_info_plist_string(app_path, "CFBundleExecutable")
+        .or_else(|| Some(app_path.file_stem()?.to_string_lossy().to_string()))?;
+    Some(app_path.join("Contents/MacOS").join(executable))
+}
+
+/// Info.plist ja lido. Com a feature `native-plist` (padrao) vem do crate
+/// `plist`, que entende plists binarios e XML; sem ela, do XML gerado pelo
+/// `plutil`.
+struct InfoPlist {
+    #[cfg(feature = "native-plist")]
+    dict: plist::Dictionary,
+    #[cfg(not(feature = "native-plist"))]
+    xml: String,
+    #[cfg(not(feature = "native-plist"))]
+    path: PathBuf,
+}
+
+impl InfoPlist {
+    /// Valor de uma chave `<string>` no nivel de cima do plist.
+    fn string(&self, key: &str) -> Option<String> {
+        #[cfg(feature = "native-plist")]
+        return self
+            .dict
+            .get(key)?
+            .as_string()
+            .map(|s| s.trim().to_string());
+        #[cfg(not(feature = "native-plist"))]
+        return plist_string_value(&self.xml, key);
+    }
+
+    /// Valor de qualquer chave do nivel de cima, convertido para JSON (arrays e
+    /// dicionarios inclusive).
+    fn value(&self, key: &str) -> Option<serde_json::Value> {
+        #[cfg(feature = "native-plist")]
+        return serde_json::to_value(self.dict.get(key)?).ok();
+        #[cfg(not(feature = "native-plist"))]
+        {
+            let output = Command::new("plutil")
+                .args(["-extract", key, "json", "-o", "-"])
+                .arg(&self.path)
+                .output()
+                .ok()?;
+            serde_json::from_slice(&output.stdout).ok()
+        }
+    }
+
+    /// Nomes das chaves (no XML, inclui as de dicionarios aninhados).
+    fn keys(&self) -> Vec<&str> {
+        #[cfg(feature = "native-plist")]
+        return self.dict.keys().map(String::as_str).collect();
+        #[cfg(not(feature = "native-plist"))]
+        return self
+            .xml
+            .split("<key>")
+            .skip(1)
+            .filter_map(|rest| rest.split_once("</key>"))
+            .map(|(key, _)| key.trim())
+            .collect();
+    }
+}
+
+#[cfg(feature = "native-plist")]
+fn parse_info_plist(app_path: &Path) -> Option<InfoPlist> {
+    let value = plist::Value::from_file(get_info_plist_path(app_path)).ok()?;
+    Some(InfoPlist {
+        dict: value.into_dictionary()?,
+    })
+}
+
+/// `plutil` converte plists binarios para XML; sem ele, so da para ler os que
+/// ja estao em XML.
+#[cfg(not(feature = "native-plist"))]
+fn parse_info_plist(app_path: &Path) -> Option<InfoPlist> {
+    let plist = get_info_plist_path(app_path);
+    if !plist.exists() {
+        return None;
+    }
+    let xml = match Command::new("plutil")
+        .args(["-convert", "xml1", "-o", "-"])
+        .arg(&plist)
+        .output()
+    {
+        Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout).to_string(),
+        _ => fs::read_to_string(&plist).ok()?,
+    };
+    Some(InfoPlist { xml, path: plist })
+}
+
+#[cfg(feature = "native-plist")]
+fn read_info_plist_string(app_path: &Path, key: &str) -> Option<String> {
+    parse_info_plist(app_path)?.string(key)
+}
+
+#[cfg(not(feature = "native-plist"))]
+fn read_info_plist_string(app_path: &Path, key: &str) -> Option<String> {
+    let plist = get_info_plist_path(app_path);
+    if !plist.exists() {
+        return None;
+    }
+
+    let output = Command::new("defaults")
+        .args(["read", &plist.to_string_lossy(), key])
+        .output();
+
+    match output {
+        Ok(o) if o.status.success() => Some(String::from_utf8_lossy(&o.stdout).trim().to_string()),
+        // Sem `defaults` (fora do macOS) ainda da para ler plists em XML.
+        _ => plist_string_value(&fs::read_to_string(&plist).ok()?, key),
+    }
+}
+
+/// Valor `<string>` que segue `<key>key</key>` em um plist XML.
+#[cfg(not(feature = "native-plist"))]
+fn plist_string_value(xml: &str, key: &str) -> Option<String> {
+    let after_key = &xml[xml.find(&format!("<key>{}</key>", key))?..];
+    let start = after_key.find("<string>")? + "<string>".len();
+    let end = start + after_key[start..].find("</string>")?;
+    Some(after_key[start..end].trim().to_string())
+}
+
+/// Resultado de `compare_installed_vs_expected`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComparisonResult {
+    /// Bundle ID, versao, executavel e assinatura conferem.
+    Match,
+    BundleIdMismatch,
+    VersionMismatch {
+        found: String,
+        expected: String,
+    },
+    /// O `CFBundleExecutable` nao existe em `Contents/MacOS`.
+    MissingExecutable,
+    /// `codesign --verify` rejeitou o bundle (alterado depois de assinado).
+    SignatureInvalid,
+}
+
+impl fmt::Display for ComparisonResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComparisonResult::Match => write!(f, "OK: o app confere com o esperado"),
+            ComparisonResult::BundleIdMismatch => write!(f, "Bundle ID diferente do esperado"),
+            ComparisonResult::VersionMismatch { found, expected } => write!(
+                f,
+                "Versao diferente: encontrada {}, esperada {}",
+                found, expected
+            ),
+            ComparisonResult::MissingExecutable => write!(f, "Executavel do app nao encontrado"),
+            ComparisonResult::SignatureInvalid => write!(f, "Assinatura de codigo invalida"),
+        }
+    }
+}
+
+/// Confere um app instalado contra o bundle ID e a versao esperados, e verifica
+/// se o executavel existe e a assinatura continua valida. Retorna o primeiro
+/// problema encontrado, nessa ordem. Versoes sao comparadas com `AppVersion`
+/// (`2.0` = `2.0.0`); sem `codesign` a assinatura nao e verificada.
+pub fn compare_installed_vs_expected(
+    app_path: &Path,
+    expected_bundle_id: &str,
+    expected_version: &str,
+) -> ComparisonResult {
+    if get_bundle_id(app_path).as_deref() != Some(expected_bundle_id) {
+        return ComparisonResult::BundleIdMismatch;
+    }
+    let found = get_app_version(app_path).unwrap_or_default();
+    let same_version = match (
+        AppVersion::parse(&found),
+        AppVersion::parse(expected_version),
+    ) {
+        (Some(a), Some(b)) => a == b,
+        _ => found.trim() == expected_version.trim(),
+    };
+    if !same_version {
+        return ComparisonResult::VersionMismatch {
+            found,
+            expected: expected_version.to_string(),
+        };
+    }
+    if !get_main_executable_path(app_path).is_some_and(|exe| exe.is_file()) {
+        return ComparisonResult::MissingExecutable;
+    }
+    if verify_code_signature(app_path) == Some(false) {
+        return ComparisonResult::SignatureInvalid;
+    }
+    ComparisonResult::Match
+}
+
+/// `codesign --verify --deep --strict`; `None` se o `codesign` nao rodou.
+pub fn verify_code_signature(app_path: &Path) -> Option<bool> {
+    Command::new("codesign")
+        .args(["--verify", "--deep", "--strict"])
+        .arg(app_path)
+        .output()
+        .ok()
+        .map(|o| o.status.success())
+}
+
+/// Team ID (10 caracteres, ex: `ABCD1234EF`) da assinatura do app, lido do
+/// requisito designado impresso por `codesign --display -r-`. Requisitos
+/// customizados nem sempre citam o `subject.OU`; nesse caso usa o
+/// `TeamIdentifier=` que o `--verbose` imprime no stderr.
+pub fn get_app_team_id(app_path: &Path) -> Option<String> {
+    let output = Command::new("codesign")
+        .args(["--display", "--verbose", "-r-", &app_path.to_string_lossy()])
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    parse_team_id(&stdout)
+        .or_else(|| parse_team_id(&stderr))
+        .or_else(|| parse_team_identifier(&stderr))
+}
+
+/// Nome do desenvolvedor, lido do certificado `Developer ID Application: <nome>
+/// (<team-id>)` da assinatura. Sem Team ID (assinatura ad-hoc ou ausente), usa
+/// o titular do copyright do Info.plist (ver `parse_copyright_holder`).
+pub fn get_app_developer(app_path: &Path) -> Option<String> {
+    let from_signature = Command::new("codesign")
+        .args(["--display", "--verbose=2", &app_path.to_string_lossy()])
+        .output()
+        .ok()
+        .and_then(|o| parse_developer_authority(&String::from_utf8_lossy(&o.stderr)));
+    from_signature.or_else(|| parse_copyright_holder(&get_app_copyright(app_path)?))
+}
+
+/// `Authority=Developer ID Application: Nome (ABCD1234EF)` -> `Nome`.
+fn parse_developer_authority(details: &str) -> Option<String> {
+    details.lines().find_map(|l| {
+        let cert = l.trim().strip_prefix("Authority=")?;
+        let rest = cert
+            .strip_prefix("Developer ID Application: ")
+            .or_else(|| cert.strip_prefix("Apple Distribution: "))?;
+        let (name, team) = rest.rsplit_once(" (")?;
+        parse_team_identifier(&format!("TeamIdentifier={}", team.strip_suffix(')')?))?;
+        Some(name.trim().to_string())
+    })
+}
+
+/// Extrai o titular de textos como `© 2024 Acme Inc. All rights reserved.`
+/// (equivalente a `(©|Copyright) \d{4}(-\d{4})? (.+)`).
+fn parse_copyright_holder(copyright: &str) -> Option<String> {
+    let start = copyright
+        .find('©')
+        .map(|i| i + '©'.len_utf8())
+        .or_else(|| copyright.find("Copyright").map(|i| i + "Copyright".len()))?;
+    let rest = copyright[start..]
+        .trim_start()
+        .trim_start_matches("(c)")
+        .trim_start();
+    let year_len = rest.chars().take_while(char::is_ascii_digit).count();
+    if year_len != 4 {
+        return None;
+    }
+    let mut rest = &rest[4..];
+    if let Some(range) = rest.strip_prefix(['-', '–']) {
+        rest = range.trim_start_matches(|c: char| c.is_ascii_digit());
+    }
+    let holder = rest.trim_start_matches(',').trim();
+    let holder = holder
+        .split_once(". All rights reserved")
+        .or_else(|| holder.split_once(", All rights reserved"))
+        .map_or(holder, |(h, _)| h)
+        .trim_end_matches('.')
+        .trim();
+    (!holder.is_empty()).then(|| holder.to_string())
+}
+
+/// `TeamIdentifier=ABCD1234EF` (ou `not set` em assinaturas ad-hoc).
+fn parse_team_identifier(details: &str) -> Option<String> {
+    let value = details
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("TeamIdentifier="))?;
+    (value.len() == 10 && value.chars().all(|c| c.is_ascii_alphanumeric()))
+        .then(|| value.to_string())
+}
+
+/// Extrai o valor de `certificate leaf[subject.OU] = <team-id>` da linha `designated =>`.
+fn parse_team_id(requirement: &str) -> Option<String> {
+    let line = requirement
+        .lines()
+        .find(|l| l.trim_start().starts_with("designated =>"))?;
+    let rest = &line[line.find("subject.OU]")? + "subject.OU]".len()..];
+    let value = rest.trim_start().strip_prefix('=')?.trim_start();
+    let team_id: String = value
+        .trim_start_matches('"')
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .collect();
+    (team_id.len() == 10).then_some(team_id)
+}
+
+/// Le o atributo de quarentena do app. Retorna `None` se o app nao esta em quarentena.
+pub fn get_quarantine_info(app_path: &Path) -> Option<QuarantineInfo> {
+    let output = Command::new("xattr")
+        .args(["-p", "com.apple.quarantine", &app_path.to_string_lossy()])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let value = String::from_utf8_lossy(&output.stdout);
+    let mut info = parse_quarantine_value(value.trim());
+    info.origin_url = get_where_from(app_path);
+    Some(info)
+}
+
+/// Interpreta o valor `flags;timestamp-hex;agente;uuid` do atributo de quarentena.
+fn parse_quarantine_value(value: &str) -> QuarantineInfo {
+    let fields: Vec<&str> = value.split(';').collect();
+    let timestamp = fields
+        .get(1)
+        .and_then(|t| u64::from_str_radix(t, 16).ok())
+        .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs));
+    let agent_bundle_id = fields
+        .get(2)
+        .filter(|a| !a.is_empty())
+        .map(|a| a.to_string());
+    QuarantineInfo {
+        origin_url: None,
+        timestamp,
+        agent_bundle_id,
+    }
+}
+
+/// Seleciona os arquivos em uma janela do Finder (`open -R`).
+pub fn reveal_in_finder(paths: &[PathBuf]) -> Result<(), RemoverError> {
+    let status = Command::new("open").arg("-R").args(paths).status()?;
+    if status.success() {
+        Ok(())
     } else {
-        format!("{} B", bytes)
+        Err(RemoverError::Io(io::Error::other(format!(
+            "open terminou com {}",
+            status
+        ))))
     }
 }
 
-pub fn get_home() -> PathBuf {
-    PathBuf::from(env::var("HOME").unwrap_or_else(|_| "/Users/unknown".to_string()))
+/// App instalado pela Mac App Store (tem recibo em `Contents/_MASReceipt`).
+pub fn is_mas_app(app_path: &Path) -> bool {
+    app_path.join("Contents/_MASReceipt/receipt").exists()
+}
+
+/// ID do app na App Store (`kMDItemAppStoreAdamID` do Spotlight).
+pub fn get_mas_app_id(app_path: &Path) -> Option<u64> {
+    let output = Command::new("mdls")
+        .args([
+            "-raw",
+            "-name",
+            "kMDItemAppStoreAdamID",
+            &app_path.to_string_lossy(),
+        ])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+/// Pagina do app na App Store, para exibir ao usuario.
+pub fn mas_page_url(app_id: u64) -> String {
+    format!("https://apps.apple.com/app/id{}", app_id)
+}
+
+/// Abre a pagina do app no app App Store (para baixar de novo depois).
+pub fn open_mas_page(app_id: u64) -> Result<(), RemoverError> {
+    let status = Command::new("open")
+        .arg(format!("macappstore://apps.apple.com/app/id{}", app_id))
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(RemoverError::Io(io::Error::other(format!(
+            "open terminou com {}",
+            status
+        ))))
+    }
+}
+
+/// Primeira URL de `kMDItemWhereFroms` (de onde o arquivo foi baixado).
+fn get_where_from(path: &Path) -> Option<String> {
+    let output = Command::new("mdls")
+        .args([
+            "-raw",
+            "-name",
+            "kMDItemWhereFroms",
+            &path.to_string_lossy(),
+        ])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let start = text.find('"')? + 1;
+    let end = text[start..].find('"')? + start;
+    Some(text[start..end].to_string()).filter(|url| !url.is_empty())
+}
+
+/// Remove o atributo de quarentena com `xattr -d com.apple.quarantine`.
+pub fn remove_quarantine_flag(path: &Path) -> io::Result<()> {
+    let output = Command::new("xattr")
+        .args(["-d", "com.apple.quarantine", &path.to_string_lossy()])
+        .output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+pub fn find_related_files(app_name: &str, bundle_id: Option<&str>) -> Vec<RelatedFile> {
+    find_related_files_with_config(app_name, bundle_id, &Config::default())
+}
+
+pub fn find_related_files_with_config(
+    app_name: &str,
+    bundle_id: Option<&str>,
+    config: &Config,
+) -> Vec<RelatedFile> {
+    find_related_files_in(&get_home(), app_name, bundle_id, config)
+}
+
+/// Como `find_related_files`, mas com os maiores arquivos primeiro.
+pub fn find_related_files_sorted_by_size(
+    app_name: &str,
+    bundle_id: Option<&str>,
+) -> Vec<RelatedFile> {
+    let mut files = find_related_files(app_name, bundle_id);
+    sort_by_size_desc(&mut files);
+    files
+}
+
+/// Ordena por tamanho decrescente; empates ficam em ordem de caminho.
+pub fn sort_by_size_desc(files: &mut [RelatedFile]) {
+    files.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+}
+
+/// Igual a `find_related_files_with_config`, usando `home` no lugar de `$HOME`.
+pub fn find_related_files_in(
+    home: &Path,
+    app_name: &str,
+    bundle_id: Option<&str>,
+    config: &Config,
+) -> Vec<RelatedFile> {
+    log_at!(
+        debug,
+        "termos de busca: app=\"{}\" bundle_id={:?}",
+        app_name,
+        bundle_id
+    );
+    let specs = default_search_specs(home, config);
+    let per_spec: Vec<Vec<(PathBuf, RelatedFileCategory)>> = specs
+        .par_iter()
+        .map(|spec| {
+            let mut spec_found = Vec::new();
+            scan_dir(
+                &spec.dir,
+                spec.depth,
+                spec,
+                app_name,
+                bundle_id,
+                &mut spec_found,
+            );
+            if let (true, Some((match_mode, category))) = (spec_found.is_empty(), spec.fallback) {
+                let fallback = SearchSpec::new(spec.dir.clone(), spec.depth, match_mode, category);
+                scan_dir(
+                    &spec.dir,
+                    spec.depth,
+                    &fallback,
+                    app_name,
+                    bundle_id,
+                    &mut spec_found,
+                );
+            }
+            spec_found
+        })
+        .collect();
+
+    // Relatado depois da busca paralela para manter a ordem das specs.
+    let mut found = Vec::new();
+    for (spec, spec_found) in specs.iter().zip(per_spec) {
+        config.report(format_args!(
+            "Varrendo {} (profundidade {})",
+            spec.dir.display(),
+            spec.depth
+        ));
+        for (path, _) in &spec_found {
+            config.report(format_args!("  encontrado: {}", path.display()));
+        }
+        found.extend(spec_found);
+    }
+
+    for plugin in find_mail_plugins_in(home, app_name) {
+        config.report(format_args!("  encontrado: {}", plugin.display()));
+        log_at!(
+            debug,
+            "encontrado: {} ({:?})",
+            plugin.display(),
+            RelatedFileCategory::MailPlugin
+        );
+        found.push((plugin, RelatedFileCategory::MailPlugin));
+    }
+    for plugin in find_contacts_plugins_in(home, app_name) {
+        config.report(format_args!("  encontrado: {}", plugin.display()));
+        found.push((plugin, RelatedFileCategory::ContactsPlugin));
+    }
+
+    if let Some(cookies) = find_cookie_store_in(home, app_name, bundle_id) {
+        config.report(format_args!("  encontrado: {}", cookies.display()));
+        found.push((cookies, RelatedFileCategory::Cookies));
+    }
+
+    for log in find_app_logs_in(home, app_name, bundle_id) {
+        config.report(format_args!("  encontrado: {}", log.display()));
+        found.push((log, RelatedFileCategory::Logs));
+    }
+
+    for diag in find_diagnostics_in(home, app_name) {
+        config.report(format_args!("  encontrado: {}", diag.display()));
+        found.push((diag, RelatedFileCategory::Diagnostics));
+    }
+
+    if let Some(team_id) = &config.team_id {
+        for group in find_group_containers_in(home, app_name, bundle_id, team_id) {
+            config.report(format_args!("  encontrado: {}", group.display()));
+            found.push((group, RelatedFileCategory::GroupContainer));
+        }
+    }
+
+    if let Some(id) = bundle_id {
+        // So as listas do proprio app; as compartilhadas (RecentApplications etc.)
+        // guardam entradas de outros apps e nao podem ser apagadas inteiras.
+        for sfl in find_sfl_files_in(home, id) {
+            if sfl.file_stem().is_some_and(|stem| stem == id) {
+                config.report(format_args!("  encontrado: {}", sfl.display()));
+                found.push((sfl, RelatedFileCategory::RecentItems));
+            }
+        }
+    }
+
+    if let Some(id) = bundle_id {
+        for support in find_nested_container_support(home, id) {
+            config.report(format_args!("  encontrado: {}", support.display()));
+            found.push((support, RelatedFileCategory::Container));
+        }
+    }
+
+    if let Some(id) = bundle_id {
+        // Tambem casam com a busca por nome em Containers; aqui ganham a categoria propria.
+        for container in find_extension_containers_in(home, id) {
+            recategorize(
+                &mut found,
+                container,
+                RelatedFileCategory::ExtensionContainer,
+                config,
+            );
+        }
+    }
+
+    // Quando o container inteiro tambem foi encontrado, `drop_nested_paths` deixa so ele.
+    if let Some(tmp) = bundle_id.and_then(|id| find_container_tmp_in(home, id)) {
+        config.report(format_args!("  encontrado: {}", tmp.display()));
+        found.push((tmp, RelatedFileCategory::TemporaryFiles));
+    }
+    if config.thorough {
+        for cache in find_caches_by_creator_in(home, app_name) {
+            if !found.iter().any(|(p, _)| *p == cache) {
+                config.report(format_args!("  encontrado: {}", cache.display()));
+                found.push((cache, RelatedFileCategory::Cache));
+            }
+        }
+    }
+
+    // A busca por nome so acha `<bundle-id>.savedState` quando o ID contem o nome.
+    if let Some(state) = bundle_id.and_then(|id| find_saved_state_in(home, id)) {
+        config.report(format_args!("  encontrado: {}", state.display()));
+        found.push((state, RelatedFileCategory::SavedState));
+    }
+    if let Some(cloudkit) = bundle_id.and_then(|id| find_cloudkit_data_in(home, id)) {
+        config.report(format_args!("  encontrado: {}", cloudkit.display()));
+        found.push((cloudkit, RelatedFileCategory::CloudKit));
+    }
+
+    if let Some(id) = bundle_id {
+        for help in find_helpd_cache_in(home, id) {
+            config.report(format_args!("  encontrado: {}", help.display()));
+            found.push((help, RelatedFileCategory::Cache));
+        }
+    }
+
+    if bundle_id == Some(XCODE_BUNDLE_ID) {
+        for cache in find_xcode_caches_in(home) {
+            recategorize(&mut found, cache, RelatedFileCategory::Developer, config);
+        }
+    }
+    if bundle_id.is_some_and(is_developer_tool) {
+        if let Some(traces) = find_instruments_cache_in(home) {
+            recategorize(&mut found, traces, RelatedFileCategory::Developer, config);
+        }
+    }
+
+    if let Some(id) = bundle_id {
+        let pref_dir = home.join("Library/Preferences");
+        let plist_file = pref_dir.join(format!("{}.plist", id));
+        if plist_file.exists() && !found.iter().any(|(p, _)| *p == plist_file) {
+            log_at!(
+                debug,
+                "encontrado: {} ({:?})",
+                plist_file.display(),
+                RelatedFileCategory::Preferences
+            );
+            config.report(format_args!("  encontrado: {}", plist_file.display()));
+            found.push((plist_file, RelatedFileCategory::Preferences));
+        }
+    }
+
+    // Mostradas dentro da entrada que as contem (ver `RelatedFile::contents`).
+    let mut highlights = Vec::new();
+    if let Some(id) = bundle_id {
+        let cache = home.join("Library/Caches").join(id);
+        let dirs = CHROMIUM_CACHE_DIRS.iter().map(|d| cache.join(d));
+        highlights.extend(dirs.filter(|d| d.is_dir()));
+    }
+    highlights.extend(find_electron_app_data_in(home, app_name));
+    // As pastas do Sparkle e do Crashlytics aparecem sozinhas, mesmo com a
+    // pasta do app encontrada inteira (ver `listed_inside_parent`).
+    for sparkle in find_sparkle_cache_in(home, app_name, bundle_id) {
+        recategorize(
+            &mut found,
+            sparkle,
+            RelatedFileCategory::UpdateCache,
+            config,
+        );
+    }
+    if let Some(id) = bundle_id {
+        for reports in find_crashlytics_data_in(home, id) {
+            recategorize(&mut found, reports, RelatedFileCategory::CrashLog, config);
+        }
+    }
+    // Bancos Realm (com seus `.lock`/`.management`) e SQLite/GRDB aparecem
+    // dentro da pasta.
+    let support_dirs = found
+        .iter()
+        .filter(|(p, c)| *c == RelatedFileCategory::AppSupport && p.is_dir());
+    for (dir, _) in support_dirs {
+        highlights.extend(find_realm_files(dir));
+        highlights.extend(find_database_files(dir));
+    }
+
+    for sidecar in sqlite_sidecars(&found) {
+        config.report(format_args!("  encontrado: {}", sidecar.0.display()));
+        found.push(sidecar);
+    }
+
+    dedup_by_canonical_path(&mut found);
+    drop_nested_paths(&mut found);
+    let mut files: Vec<RelatedFile> = found
+        .into_par_iter()
+        .map(|(path, category)| {
+            let size = dir_size(&path).unwrap_or(0);
+            let requires_elevation = requires_elevation(&path);
+            RelatedFile {
+                path,
+                category,
+                size,
+                requires_elevation,
+                contents: Vec::new(),
+            }
+        })
+        .collect();
+    exclude_nested_sizes(&mut files);
+    attach_contents(&mut files, highlights);
+    files
+}
+
+/// Poe cada caminho de `highlights` em `contents` da entrada mais proxima que
+/// o contem. Os que nao estao dentro de nenhuma entrada sao ignorados.
+fn attach_contents(files: &mut [RelatedFile], highlights: Vec<PathBuf>) {
+    for path in highlights {
+        let parent = files
+            .iter_mut()
+            .filter(|f| path.starts_with(&f.path) && f.path != path)
+            .max_by_key(|f| f.path.components().count());
+        if let Some(parent) = parent {
+            let size = dir_size(&path).unwrap_or(0);
+            parent.contents.push((path, size));
+        }
+    }
+}
+
+/// Subpastas que o Chromium (Electron, web wrappers como Slack e Discord) cria
+/// no cache do app e que costumam ser grandes.
+const CHROMIUM_CACHE_DIRS: &[&str] = &["blob_storage", "GPUCache", "IndexedDB"];
+
+/// Subpastas de ~/Library/Application Support/<AppName> tipicas do Electron.
+const ELECTRON_SUPPORT_DIRS: &[&str] =
+    &["Cache", "Code Cache", "GPUCache", "Service Worker", "logs"];
+
+/// Subpastas Electron (`Cache`, `Code Cache`...) de
+/// ~/Library/Application Support/<app_name> que existem.
+pub fn find_electron_app_data(app_name: &str) -> Vec<PathBuf> {
+    find_electron_app_data_in(&get_home(), app_name)
+}
+
+fn find_electron_app_data_in(home: &Path, app_name: &str) -> Vec<PathBuf> {
+    let support = home.join("Library/Application Support").join(app_name);
+    ELECTRON_SUPPORT_DIRS
+        .iter()
+        .map(|d| support.join(d))
+        .filter(|d| d.is_dir())
+        .collect()
+}
+
+/// Extensoes que o Realm cria ao lado de cada banco `<nome>.realm`.
+const REALM_COMPANIONS: &[&str] = &["realm.lock", "realm.management", "realm.note"];
+
+/// Bancos Realm (`*.realm`) direto em `dir`, cada um seguido dos arquivos
+/// `.realm.lock`/`.realm.management`/`.realm.note` que existirem.
+pub fn find_realm_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut databases: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "realm") && p.is_file())
+        .collect();
+    databases.sort();
+    let mut found = Vec::new();
+    for db in databases {
+        let companions: Vec<PathBuf> = REALM_COMPANIONS
+            .iter()
+            .map(|ext| db.with_extension(ext))
+            .filter(|p| p.exists())
+            .collect();
+        found.push(db);
+        found.extend(companions);
+    }
+    found
+}
+
+/// Onde o Sparkle guarda as atualizacoes baixadas: `Application
+/// Support/<AppName>/Sparkle` e `Caches/<bundle-id>/org.sparkle-project.Sparkle`.
+/// Costuma ter o ultimo DMG baixado, com centenas de MB.
+pub fn find_sparkle_cache(app_name: &str, bundle_id: Option<&str>) -> Vec<PathBuf> {
+    find_sparkle_cache_in(&get_home(), app_name, bundle_id)
+}
+
+fn find_sparkle_cache_in(home: &Path, app_name: &str, bundle_id: Option<&str>) -> Vec<PathBuf> {
+    let lib = home.join("Library");
+    let support = lib
+        .join("Application Support")
+        .join(app_name)
+        .join("Sparkle");
+    let cache = bundle_id.map(|id| {
+        lib.join("Caches")
+            .join(id)
+            .join("org.sparkle-project.Sparkle")
+    });
+    std::iter::once(support)
+        .chain(cache)
+        .filter(|dir| dir.is_dir())
+        .collect()
+}
+
+/// Subpastas de `Caches/<bundle-id>` onde o SDK do Crashlytics (Firebase) guarda
+/// relatorios de falha ainda nao enviados.
+const CRASHLYTICS_DIRS: &[&str] = &["com.crashlytics", "com.crashlytics.data"];
+
+/// Relatorios pendentes do Crashlytics em ~/Library/Caches/<bundle_id>. Depois
+/// de remover o app nunca serao enviados; em apps que falham muito acumulam
+/// bastante espaco.
+pub fn find_crashlytics_data(bundle_id: &str) -> Vec<PathBuf> {
+    find_crashlytics_data_in(&get_home(), bundle_id)
+}
+
+fn find_crashlytics_data_in(home: &Path, bundle_id: &str) -> Vec<PathBuf> {
+    let cache = home.join("Library/Caches").join(bundle_id);
+    CRASHLYTICS_DIRS
+        .iter()
+        .map(|d| cache.join(d))
+        .filter(|d| d.is_dir())
+        .collect()
+}
+
+/// Extensoes de bancos SQLite, inclusive os criados pelo GRDB.
+const DATABASE_EXTENSIONS: &[&str] = &["sqlite", "db", "grdb"];
+
+/// Bancos `.sqlite`/`.db`/`.grdb` em qualquer nivel de `dir` (sem seguir
+/// symlinks), em ordem.
+pub fn find_database_files(dir: &Path) -> Vec<PathBuf> {
+    fn collect(dir: &Path, found: &mut Vec<PathBuf>) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for path in entries.flatten().map(|e| e.path()) {
+            if is_symlink(&path) {
+                continue;
+            }
+            if path.is_dir() {
+                collect(&path, found);
+            } else if path
+                .extension()
+                .is_some_and(|ext| DATABASE_EXTENSIONS.iter().any(|d| ext == *d))
+            {
+                found.push(path);
+            }
+        }
+    }
+    let mut found = Vec::new();
+    collect(dir, &mut found);
+    found.sort();
+    found
+}
+
+/// Arquivos `-wal`/`-shm` que o SQLite deixa ao lado de um `.sqlite`/`.db` ja
+/// encontrado quando o app e encerrado a forca. Nem sempre contem o nome do app.
+fn sqlite_sidecars(
+    found: &[(PathBuf, RelatedFileCategory)],
+) -> Vec<(PathBuf, RelatedFileCategory)> {
+    let mut sidecars = Vec::new();
+    for (path, category) in found {
+        let is_db = matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("sqlite" | "db")
+        );
+        if !is_db || !path.is_file() {
+            continue;
+        }
+        for suffix in ["-wal", "-shm"] {
+            let mut name = path.as_os_str().to_owned();
+            name.push(suffix);
+            let sidecar = PathBuf::from(name);
+            if sidecar.exists() && !found.iter().any(|(p, _)| *p == sidecar) {
+                sidecars.push((sidecar, *category));
+            }
+        }
+    }
+    sidecars
+}
+
+/// Inclui `path` com `category`, trocando a categoria se uma busca generica ja o encontrou.
+fn recategorize(
+    found: &mut Vec<(PathBuf, RelatedFileCategory)>,
+    path: PathBuf,
+    category: RelatedFileCategory,
+    config: &Config,
+) {
+    let before = found.len();
+    found.retain(|(p, _)| *p != path);
+    if found.len() == before {
+        config.report(format_args!("  encontrado: {}", path.display()));
+    }
+    found.push((path, category));
+}
+
+/// Remove entradas que apontam para o mesmo arquivo (ex: symlinks em ~/Library/Containers),
+/// mantendo o caminho original para exibicao e preferindo o caminho real ao symlink:
+/// `remove_path` em um symlink apagaria so o link, nao os dados.
+fn dedup_by_canonical_path(found: &mut Vec<(PathBuf, RelatedFileCategory)>) {
+    let mut keyed: Vec<(PathBuf, bool, PathBuf, RelatedFileCategory)> = found
+        .drain(..)
+        .map(|(path, category)| {
+            let canonical = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+            let is_alias = is_symlink(&path);
+            (canonical, is_alias, path, category)
+        })
+        .collect();
+    keyed.sort_by(|a, b| (&a.0, a.1, &a.2).cmp(&(&b.0, b.1, &b.2)));
+    keyed.dedup_by(|a, b| a.0 == b.0);
+    found.extend(
+        keyed
+            .into_iter()
+            .map(|(_, _, path, category)| (path, category)),
+    );
+    found.sort_by(|a, b| a.0.cmp(&b.0));
+}
+
+/// Remove entradas dentro de outra entrada ja encontrada: seriam apagadas junto
+/// com ela e contariam duas vezes no tamanho. As de categorias
+/// `listed_inside_parent` ficam; `exclude_nested_sizes` tira o tamanho delas da
+/// entrada de fora. `found` deve estar ordenado.
+fn drop_nested_paths(found: &mut Vec<(PathBuf, RelatedFileCategory)>) {
+    let mut outer: Option<PathBuf> = None;
+    found.retain(|(path, category)| {
+        if outer
+            .as_ref()
+            .is_some_and(|parent| path.starts_with(parent))
+        {
+            return category.listed_inside_parent();
+        }
+        outer = Some(path.clone());
+        true
+    });
+}
+
+/// Desconta o tamanho de cada entrada da entrada mais proxima que a contem,
+/// para o total nao contar duas vezes.
+fn exclude_nested_sizes(files: &mut [RelatedFile]) {
+    for i in 0..files.len() {
+        let parent = (0..files.len())
+            .filter(|&j| j != i && files[i].path.starts_with(&files[j].path))
+            .max_by_key(|&j| files[j].path.components().count());
+        if let Some(j) = parent {
+            files[j].size = files[j].size.saturating_sub(files[i].size);
+        }
+    }
+}
+
+/// Quantas outras entradas de `paths` contem `path`.
+fn nesting_depth<'a>(path: &Path, paths: impl IntoIterator<Item = &'a PathBuf>) -> usize {
+    paths
+        .into_iter()
+        .filter(|other| other.as_path() != path && path.starts_with(other))
+        .count()
+}
+
+/// `related` na ordem de remocao: entradas dentro de outras (ex: o CloudKit
+/// dentro do container) antes das que as contem, que levariam as de dentro
+/// junto. Fora isso a ordem e mantida.
+fn removal_order(related: &[RelatedFile]) -> Vec<&RelatedFile> {
+    let mut ordered: Vec<&RelatedFile> = related.iter().collect();
+    ordered.sort_by_key(|f| {
+        std::cmp::Reverse(nesting_depth(&f.path, related.iter().map(|r| &r.path)))
+    });
+    ordered
+}
+
+/// Group Containers do app em ~/Library/Group Containers. Os do desenvolvedor
+/// sao identificados pelo Team ID (`ABCD1234EF.com.example.shared`), que e mais
+/// confiavel que o nome do app; os da App Store (`group.<bundle-id>`) pelo
+/// bundle ID. So quando nenhum casa o nome do app e usado, como sem Team ID.
+/// Podem ser compartilhados com outros apps do mesmo desenvolvedor.
+pub fn find_group_containers(
+    app_name: &str,
+    bundle_id: Option<&str>,
+    team_id: &str,
+) -> Vec<PathBuf> {
+    find_group_containers_in(&get_home(), app_name, bundle_id, team_id)
+}
+
+fn find_group_containers_in(
+    home: &Path,
+    app_name: &str,
+    bundle_id: Option<&str>,
+    team_id: &str,
+) -> Vec<PathBuf> {
+    let mut found = find_group_containers_by_team(home, team_id);
+    if let Some(id) = bundle_id {
+        let prefix = format!("group.{}", id);
+        found.extend(
+            fs::read_dir(home.join("Library/Group Containers"))
+                .into_iter()
+                .flatten()
+                .flatten()
+                .filter(|e| e.file_name().to_string_lossy().starts_with(&prefix))
+                .map(|e| e.path()),
+        );
+    }
+    if found.is_empty() {
+        let spec = SearchSpec::new(
+            home.join("Library/Group Containers"),
+            1,
+            MatchMode::SubstringAny,
+            RelatedFileCategory::GroupContainer,
+        );
+        let mut by_name = Vec::new();
+        scan_dir(
+            &spec.dir,
+            spec.depth,
+            &spec,
+            app_name,
+            bundle_id,
+            &mut by_name,
+        );
+        found.extend(by_name.into_iter().map(|(path, _)| path));
+    }
+    found.sort();
+    found
+}
+
+/// Group Containers identificados pelo Team ID (`ABCD1234EF.com.example.shared`),
+/// que nem sempre contem o nome do app.
+fn find_group_containers_by_team(home: &Path, team_id: &str) -> Vec<PathBuf> {
+    let prefix = format!("{}.", team_id);
+    let mut found = Vec::new();
+    if let Ok(entries) = fs::read_dir(home.join("Library/Group Containers")) {
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with(&prefix) {
+                found.push(entry.path());
+            }
+        }
+    }
+    found
+}
+
+/// `~/Library/Containers/<bundle_id>/Data/tmp`, se existir. Acumula arquivos
+/// temporarios que podem ser apagados mesmo sem remover o app.
+pub fn find_container_tmp(bundle_id: &str) -> Option<PathBuf> {
+    find_container_tmp_in(&get_home(), bundle_id)
+}
+
+fn find_container_tmp_in(home: &Path, bundle_id: &str) -> Option<PathBuf> {
+    let tmp = home
+        .join("Library/Containers")
+        .join(bundle_id)
+        .join("Data/tmp");
+    tmp.is_dir().then_some(tmp)
+}
+
+/// `~/Library/Saved Application State/<bundle_id>.savedState`, se existir. Apps
+/// que restauram janelas (ex: Xcode, com o estado de todos os editores) podem
+/// deixar dezenas de MB aqui.
+pub fn find_saved_state(bundle_id: &str) -> Option<PathBuf> {
+    find_saved_state_in(&get_home(), bundle_id)
+}
+
+fn find_saved_state_in(home: &Path, bundle_id: &str) -> Option<PathBuf> {
+    let state = home
+        .join("Library/Saved Application State")
+        .join(format!("{}.savedState", bundle_id));
+    state.is_dir().then_some(state)
+}
+
+/// Apaga a chave `NSWindowFrameAutosaveName` das preferencias do app, usada por
+/// apps com restauracao de janelas. Chamado depois de remover o estado salvo,
+/// para o caso de as preferencias serem mantidas.
+pub fn clear_window_restoration(bundle_id: &str) {
+    let _ = Command::new("defaults")
+        .args(["delete", bundle_id, "NSWindowFrameAutosaveName"])
+        .output();
+}
+
+/// O estado salvo foi removido e o plist de preferencias ficou. Se o plist
+/// tambem foi apagado nao ha chave para limpar, e o `defaults delete` faria o
+/// `cfprefsd` grava-lo de novo.
+fn needs_window_restoration_cleared(bundle_id: &str, removed: &[&RelatedFile]) -> bool {
+    let plist = format!("{}.plist", bundle_id);
+    let prefs_removed = removed.iter().any(|f| {
+        f.category == RelatedFileCategory::Preferences
+            && f.path
+                .file_name()
+                .is_some_and(|name| name == plist.as_str())
+    });
+    !prefs_removed
+        && removed
+            .iter()
+            .any(|f| f.category == RelatedFileCategory::SavedState)
+}
+
+/// Tira o app do banco do Launch Services (`lsregister -u`). Sem isso, os
+/// esquemas de URL e tipos de documento do app continuam registrados depois de
+/// remove-lo.
+pub fn unregister_app_handlers(app_path: &Path) {
+    let _ = Command::new(LSREGISTER).arg("-u").arg(app_path).output();
+}
+
+/// Tira das preferencias do Launch Services os handlers (`LSHandlers`) que
+/// citam o app pelo caminho ou pelo bundle ID. Depois do app removido o bundle
+/// ID nao pode mais ser lido do Info.plist, por isso vem do plano. Os plists
+/// pertencem ao `cfprefsd`, entao sao lidos e gravados com `defaults
+/// export`/`import`. Retorna quantos handlers foram removidos; o banco so muda
+/// de fato com `rebuild_launch_services`. Sem a feature `native-plist` nada e
+/// alterado.
+pub fn unregister_launch_services(app_path: &Path, bundle_id: Option<&str>) -> usize {
+    let prefs = get_home().join("Library/Preferences");
+    [
+        prefs.join("com.apple.LaunchServices"),
+        prefs.join("com.apple.LaunchServices/com.apple.launchservices.secure"),
+    ]
+    .iter()
+    .map(|domain| clean_launch_services_domain(domain, app_path, bundle_id))
+    .sum()
+}
+
+/// Reconstroi o banco do Launch Services (`lsregister -kill -r`), o que pode
+/// levar alguns segundos.
+pub fn rebuild_launch_services() {
+    let _ = Command::new(LSREGISTER)
+        .args([
+            "-kill", "-r", "-domain", "local", "-domain", "system", "-domain", "user",
+        ])
+        .output();
+}
+
+/// `domain` e o caminho do plist sem a extensao, como o `defaults` aceita.
+#[cfg(feature = "native-plist")]
+fn clean_launch_services_domain(domain: &Path, app_path: &Path, bundle_id: Option<&str>) -> usize {
+    let Ok(output) = Command::new("defaults")
+        .arg("export")
+        .arg(domain)
+        .arg("-")
+        .output()
+    else {
+        return 0;
+    };
+    if !output.status.success() {
+        return 0;
+    }
+    let Ok(mut value) = plist::Value::from_reader(io::Cursor::new(output.stdout)) else {
+        return 0;
+    };
+    let removed = remove_launch_services_handlers(&mut value, app_path, bundle_id);
+    let mut xml = Vec::new();
+    if removed == 0 || value.to_writer_xml(&mut xml).is_err() {
+        return 0;
+    }
+    let import = Command::new("defaults")
+        .arg("import")
+        .arg(domain)
+        .arg("-")
+        .stdin(Stdio::piped())
+        .spawn()
+        .and_then(|mut child| {
+            child
+                .stdin
+                .take()
+                .map(|mut stdin| stdin.write_all(&xml))
+                .transpose()?;
+            child.wait()
+        });
+    match import {
+        Ok(status) if status.success() => removed,
+        _ => 0,
+    }
+}
+
+#[cfg(not(feature = "native-plist"))]
+fn clean_launch_services_domain(
+    _domain: &Path,
+    _app_path: &Path,
+    _bundle_id: Option<&str>,
+) -> usize {
+    0
+}
+
+/// `(bundle, bundle ID)` dos apps em `dirs` e subpastas, lido so do Info.plist
+/// (sem tamanho nem Spotlight, ao contrario de `get_installed_app_infos`).
+fn installed_bundle_ids_in(dirs: &[PathBuf]) -> Vec<(PathBuf, String)> {
+    get_installed_apps_in(dirs, true)
+        .into_iter()
+        .filter_map(|path| get_bundle_id(&path).map(|id| (path, id)))
+        .collect()
+}
+
+/// Outro app instalado usa o mesmo bundle ID; os handlers do Launch Services
+/// continuam valendo para ele.
+fn has_other_copy_installed(
+    installed: &[(PathBuf, String)],
+    app_path: &Path,
+    bundle_id: &str,
+) -> bool {
+    installed
+        .iter()
+        .any(|(path, id)| id == bundle_id && path != app_path)
+}
+
+#[cfg(feature = "native-plist")]
+fn remove_launch_services_handlers(
+    value: &mut plist::Value,
+    app_path: &Path,
+    bundle_id: Option<&str>,
+) -> usize {
+    fn references_app(value: &plist::Value, app_path: &str, bundle_id: Option<&str>) -> bool {
+        match value {
+            plist::Value::String(s) => {
+                bundle_id.is_some_and(|id| s.eq_ignore_ascii_case(id)) || s.contains(app_path)
+            }
+            plist::Value::Dictionary(d) => {
+                d.values().any(|v| references_app(v, app_path, bundle_id))
+            }
+            plist::Value::Array(a) => a.iter().any(|v| references_app(v, app_path, bundle_id)),
+            _ => false,
+        }
+    }
+    let handlers = value
+        .as_dictionary_mut()
+        .and_then(|d| d.get_mut("LSHandlers"))
+        .and_then(|h| h.as_array_mut());
+    let Some(handlers) = handlers else {
+        return 0;
+    };
+    let app_path = app_path.to_string_lossy();
+    let before = handlers.len();
+    handlers.retain(|h| !references_app(h, &app_path, bundle_id));
+    before - handlers.len()
+}
+
+const LSREGISTER: &str = "/System/Library/Frameworks/CoreServices.framework/Frameworks/\
+                          LaunchServices.framework/Support/lsregister";
+
+/// Dados do Help Viewer para o app em ~/Library/Caches/com.apple.helpd (ex:
+/// `Generated/com.example.app.help*1.0`), cujo nome contem o bundle ID. Apps
+/// grandes como o Xcode chegam a centenas de MB; o helpd recria o que precisar,
+/// entao apagar nao tem risco.
+pub fn find_helpd_cache(bundle_id: &str) -> Vec<PathBuf> {
+    find_helpd_cache_in(&get_home(), bundle_id)
+}
+
+fn find_helpd_cache_in(home: &Path, bundle_id: &str) -> Vec<PathBuf> {
+    fn collect(dir: &Path, depth: usize, needle: &str, found: &mut Vec<PathBuf>) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if entry
+                .file_name()
+                .to_string_lossy()
+                .to_lowercase()
+                .contains(needle)
+            {
+                found.push(path);
+            } else if depth > 1 && path.is_dir() {
+                collect(&path, depth - 1, needle, found);
+            }
+        }
+    }
+    let mut found = Vec::new();
+    let helpd = home.join("Library/Caches/com.apple.helpd");
+    // ex: SDMHelpData/Other/English/HelpSDMIndexFile/<bundle-id>.help*1.0
+    collect(&helpd, 5, &bundle_id.to_lowercase(), &mut found);
+    found.sort();
+    found
+}
+
+/// Copia local dos dados do CloudKit do app, em
+/// `Containers/<bundle_id>/Data/Library/Application Support/CloudKit`.
+pub fn find_cloudkit_data(bundle_id: &str) -> Option<PathBuf> {
+    find_cloudkit_data_in(&get_home(), bundle_id)
+}
+
+fn find_cloudkit_data_in(home: &Path, bundle_id: &str) -> Option<PathBuf> {
+    let cloudkit = home
+        .join("Library/Containers")
+        .join(bundle_id)
+        .join("Data/Library/Application Support/CloudKit");
+    cloudkit.is_dir().then_some(cloudkit)
+}
+
+const XCODE_BUNDLE_ID: &str = "com.apple.dt.Xcode";
+
+/// Caches do Xcode e das ferramentas que vem com ele (Simulator, Instruments)
+/// que existem neste usuario.
+pub fn find_xcode_caches() -> Vec<PathBuf> {
+    find_xcode_caches_in(&get_home())
+}
+
+fn find_xcode_caches_in(home: &Path) -> Vec<PathBuf> {
+    [
+        "Library/Developer/Xcode/DerivedData",
+        "Library/Caches/com.apple.dt.Xcode",
+        "Library/Developer/CoreSimulator/Caches",
+        "Library/Developer/Xcode/iOS DeviceSupport",
+    ]
+    .iter()
+    .map(|dir| home.join(dir))
+    .filter(|dir| dir.exists())
+    .collect()
+}
+
+/// Ferramentas de desenvolvimento da Apple (Xcode, Instruments...), pelo bundle ID.
+fn is_developer_tool(bundle_id: &str) -> bool {
+    let id = bundle_id.to_lowercase();
+    id.starts_with("com.apple.dt") || id.starts_with("com.apple.xcode")
+}
+
+/// `~/Library/Caches/com.apple.dt.instruments`, com traces e simbolos das sessoes
+/// de profiling. So entra na busca de ferramentas de desenvolvimento da Apple.
+/// Pode ser apagado sem risco: o Instruments recria o que precisar e nenhum app
+/// instalado depende dele.
+pub fn find_instruments_cache() -> Option<PathBuf> {
+    find_instruments_cache_in(&get_home())
+}
+
+fn find_instruments_cache_in(home: &Path) -> Option<PathBuf> {
+    let cache = home.join("Library/Caches/com.apple.dt.instruments");
+    cache.is_dir().then_some(cache)
+}
+
+/// Containers de extensoes do app (compartilhamento, acoes...), que tem bundle
+/// ID `<parent_bundle_id>.*` e continuam em ~/Library/Containers depois que o
+/// app e removido.
+pub fn find_extension_containers(parent_bundle_id: &str) -> Vec<PathBuf> {
+    find_extension_containers_in(&get_home(), parent_bundle_id)
+}
+
+fn find_extension_containers_in(home: &Path, parent_bundle_id: &str) -> Vec<PathBuf> {
+    let prefix = format!("{}.", parent_bundle_id.to_lowercase());
+    let mut found = Vec::new();
+    if let Ok(entries) = fs::read_dir(home.join("Library/Containers")) {
+        for entry in entries.flatten() {
+            if entry
+                .file_name()
+                .to_string_lossy()
+                .to_lowercase()
+                .starts_with(&prefix)
+            {
+                found.push(entry.path());
+            }
+        }
+    }
+    found.sort();
+    found
+}
+
+/// Dados de servicos XPC e extensoes guardados dentro de outro container, em
+/// `Containers/<container>/Data/Library/Application Support/<bundle-id>...`
+/// (ex: extensoes do Safari no container do Safari).
+fn find_nested_container_support(home: &Path, bundle_id: &str) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let containers = match fs::read_dir(home.join("Library/Containers")) {
+        Ok(entries) => entries,
+        Err(_) => return found,
+    };
+    for container in containers.flatten() {
+        let support = container.path().join("Data/Library/Application Support");
+        if let Ok(entries) = fs::read_dir(&support) {
+            for entry in entries.flatten() {
+                let entry_name = entry.file_name().to_string_lossy().to_string();
+                if MatchMode::PrefixBundleId.matches(&entry_name, "", Some(bundle_id)) {
+                    found.push(entry.path());
+                }
+            }
+        }
+    }
+    found
+}
+
+/// Containers em ~/Library/Containers cujo bundle ID nao pertence a nenhum app
+/// instalado (nem a uma extensao dele, `<bundle-id>.*`). Retorna pares
+/// `(caminho, bundle_id)`. Containers `com.apple.*` sao ignorados: muitos sao de
+/// servicos do sistema sem `.app`.
+pub fn scan_orphaned_containers() -> Vec<(PathBuf, String)> {
+    let installed: HashSet<String> = get_installed_app_infos()
+        .into_iter()
+        .filter_map(|app| app.bundle_id)
+        .collect();
+    orphaned_containers_in(&get_home().join("Library/Containers"), &installed)
+}
+
+fn orphaned_containers_in(
+    containers: &Path,
+    installed: &HashSet<String>,
+) -> Vec<(PathBuf, String)> {
+    let installed: Vec<String> = installed.iter().map(|id| id.to_lowercase()).collect();
+    let mut orphans: Vec<(PathBuf, String)> = match fs::read_dir(containers) {
+        Ok(entries) => entries
+            .flatten()
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| {
+                (
+                    entry.path(),
+                    entry.file_name().to_string_lossy().to_string(),
+                )
+            })
+            .filter(|(_, id)| !id.to_lowercase().starts_with("com.apple."))
+            .filter(|(_, id)| {
+                let id = id.to_lowercase();
+                !installed
+                    .iter()
+                    .any(|app| id == *app || id.starts_with(&format!("{}.", app)))
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    orphans.sort();
+    orphans
+}
+
+/// Diretorios varridos por `find_related_files`, com a profundidade e o criterio de cada um.
+fn default_search_specs(home: &Path, config: &Config) -> Vec<SearchSpec> {
+    use MatchMode::*;
+    use RelatedFileCategory as C;
+
+    let lib = home.join("Library");
+    let mut specs = vec![
+        SearchSpec::new(
+            lib.join("Application Support"),
+            1,
+            SubstringAny,
+            C::AppSupport,
+        ),
+        // PLCrashReporter e afins: <AppName>_<UUID>.plcrash
+        SearchSpec::new(
+            lib.join("Application Support/CrashReporter"),
+            1,
+            SubstringAny,
+            C::CrashLog,
+        ),
+        // Dados do antigo Sync Services (MobileMe/iCloud, ~2012-2016), ex:
+        // SyncServices/Local/clientdata/<bundle-id>; o macOS nao usa mais
+        SearchSpec::new(
+            lib.join("Application Support/SyncServices"),
+            3,
+            SubstringAny,
+            C::SyncServices,
+        ),
+        SearchSpec::new(lib.join("Caches"), 1, SubstringAny, C::Cache),
+        // ex: Caches/<vendor>/com.example.app.helper
+        SearchSpec::new(lib.join("Caches"), 2, PrefixBundleId, C::Cache),
+        // Downloads de URLSession em segundo plano, por bundle ID
+        SearchSpec::new(
+            lib.join("com.apple.nsurlsessiond"),
+            2,
+            ExactBundleId,
+            C::NetworkData,
+        ),
+        // Caches de metadados de projetos (iWork, Final Cut Pro), as vezes com gigabytes
+        SearchSpec::new(lib.join("Caches/Metadata"), 2, PrefixBundleId, C::Cache),
+        SearchSpec::new(lib.join("Preferences"), 1, SubstringAny, C::Preferences),
+        // Preferencias por maquina: <bundle-id>.<UUID do hardware>.plist
+        SearchSpec::new(
+            lib.join("Preferences/ByHost"),
+            1,
+            PrefixBundleId,
+            C::Preferences,
+        ),
+        SearchSpec::new(lib.join("Containers"), 1, SubstringAny, C::Container),
+        SearchSpec::new(lib.join("Containers"), 2, PrefixBundleId, C::Container),
+        SearchSpec::new(
+            lib.join("Saved Application State"),
+            1,
+            SubstringAny,
+            C::SavedState,
+        ),
+        // WebKit e HTTPStorages guardam os dados de cada app em um diretorio com o
+        // bundle ID exato; o nome do app so e usado no WebKit se nao houver nenhum
+        SearchSpec::new(lib.join("WebKit"), 1, ExactBundleId, C::WebData)
+            .with_fallback(SubstringAny, C::WebDataByName),
+        // So pelo bundle ID: pelo nome, "Safari" casaria com com.apple.Safari
+        SearchSpec::new(lib.join("HTTPStorages"), 1, ExactBundleId, C::WebData),
+        // Dicionarios personalizados (ex: <App>.dic, <App>-terms)
+        SearchSpec::new(lib.join("Spelling"), 1, SubstringAny, C::Spelling),
+        // Biome guarda streams de uso em Biome/streams/<tipo>/<bundle-id>...
+        SearchSpec::new(lib.join("Biome"), 3, PrefixBundleId, C::Biome),
+    ];
+
+    // Com o Team ID, `find_group_containers` busca pelo prefixo em vez do nome.
+    if config.team_id.is_none() {
+        specs.push(SearchSpec::new(
+            lib.join("Group Containers"),
+            1,
+            SubstringAny,
+            C::GroupContainer,
+        ));
+    }
+
+    if config.search_system_library {
+        specs.extend(system_library_specs(Path::new("/Library")));
+    }
+
+    specs
+}
+
+/// Pastas de /Library varridas com `Config::search_system_library`.
+fn system_library_specs(library: &Path) -> [SearchSpec; 4] {
+    use MatchMode::SubstringAny;
+    use RelatedFileCategory as C;
+    [
+        SearchSpec::new(
+            library.join("Application Support"),
+            1,
+            SubstringAny,
+            C::AppSupport,
+        ),
+        SearchSpec::new(library.join("Caches"), 1, SubstringAny, C::Cache),
+        SearchSpec::new(library.join("Logs"), 1, SubstringAny, C::Logs),
+        SearchSpec::new(library.join("Preferences"), 1, SubstringAny, C::Preferences),
+    ]
+}
+
+/// Dados do app em /Library/Application Support, compartilhados por todos os
+/// usuarios (Parallels, VMware Fusion e Adobe guardam gigabytes ali). E a
+/// mesma spec que `Config::search_system_library` acrescenta a busca; tudo
+/// exige `sudo` para remover.
+pub fn find_system_library_support(app_name: &str, bundle_id: Option<&str>) -> Vec<RelatedFile> {
+    find_system_library_support_in(Path::new("/Library"), app_name, bundle_id)
+}
+
+fn find_system_library_support_in(
+    library: &Path,
+    app_name: &str,
+    bundle_id: Option<&str>,
+) -> Vec<RelatedFile> {
+    let [spec, ..] = system_library_specs(library);
+    let mut found = Vec::new();
+    scan_dir(
+        &spec.dir, spec.depth, &spec, app_name, bundle_id, &mut found,
+    );
+    found.sort_by(|a, b| a.0.cmp(&b.0));
+    found
+        .into_iter()
+        .map(|(path, category)| RelatedFile {
+            size: dir_size(&path).unwrap_or(0),
+            path,
+            category,
+            requires_elevation: true,
+            contents: Vec::new(),
+        })
+        .collect()
+}
+
+/// Varre `dir` ate `depth` niveis; entradas que casam sao incluidas inteiras,
+/// as demais (se forem diretorios) sao exploradas no nivel seguinte.
+fn scan_dir(
+    dir: &Path,
+    depth: usize,
+    spec: &SearchSpec,
+    app_name: &str,
+    bundle_id: Option<&str>,
+    found: &mut Vec<(PathBuf, RelatedFileCategory)>,
+) {
+    if depth == 0 {
+        return;
+    }
+    log_at!(
+        debug,
+        "varrendo {} (profundidade {}, {:?})",
+        dir.display(),
+        depth,
+        spec.match_mode
+    );
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let entry_name = entry.file_name().to_string_lossy().to_string();
+            let path = entry.path();
+            if spec.match_mode.matches(&entry_name, app_name, bundle_id) {
+                log_at!(
+                    debug,
+                    "encontrado: {} ({:?})",
+                    path.display(),
+                    spec.category
+                );
+                found.push((path, spec.category));
+            } else if depth > 1 && path.is_dir() {
+                scan_dir(&path, depth - 1, spec, app_name, bundle_id, found);
+            }
+        }
+    }
+}
+
+/// Ferramentas de linha de comando que o app instalou em /usr/local/bin,
+/// ~/.local/bin ou /opt/homebrew/bin: links para dentro do proprio bundle (ex:
+/// `code` -> `Visual Studio Code.app/...`). Executaveis que so tem o nome do app
+/// ficam em `find_cli_tools_by_name`, porque podem ser de outra origem.
+pub fn find_installed_cli_tools(app_name: &str, bundle_id: &str) -> Vec<PathBuf> {
+    find_installed_cli_tools_in(&cli_tool_dirs(), app_name, bundle_id).0
+}
+
+/// Executaveis (ou links que nao apontam para nenhum app) nas mesmas pastas de
+/// `find_installed_cli_tools` cujo nome contem o nome do app (sem espacos) ou o
+/// fim do bundle ID (`slackmacgap`). Nao entram no plano, so num aviso
+/// (`Warning::CliToolNameMatch`). Links para o Cellar/Caskroom do Homebrew
+/// ficam de fora: pertencem as formulas.
+pub fn find_cli_tools_by_name(app_name: &str, bundle_id: &str) -> Vec<PathBuf> {
+    find_installed_cli_tools_in(&cli_tool_dirs(), app_name, bundle_id).1
+}
+
+fn cli_tool_dirs() -> [PathBuf; 3] {
+    [
+        PathBuf::from("/usr/local/bin"),
+        get_home().join(".local/bin"),
+        PathBuf::from("/opt/homebrew/bin"),
+    ]
+}
+
+/// `(links para o bundle do app, executaveis so com o nome dele)`.
+fn find_installed_cli_tools_in(
+    dirs: &[PathBuf],
+    app_name: &str,
+    bundle_id: &str,
+) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    use std::os::unix::fs::PermissionsExt;
+
+    let app = app_name.replace(' ', "").to_lowercase();
+    let suffix = bundle_id
+        .rsplit('.')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    let bundle = format!("{}.app", app_name.to_lowercase());
+    let name_matches = |name: &str| {
+        let name = name.to_lowercase();
+        (app.len() >= 3 && name.contains(&app)) || (suffix.len() >= 4 && name.contains(&suffix))
+    };
+
+    let (mut linked, mut by_name) = (Vec::new(), Vec::new());
+    for entry in dirs
+        .iter()
+        .flat_map(|d| fs::read_dir(d).into_iter().flatten().flatten())
+    {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Ok(target) = fs::read_link(&path) else {
+            let executable = fs::metadata(&path)
+                .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0);
+            if executable && name_matches(&name) {
+                by_name.push(path);
+            }
+            continue;
+        };
+        // Links relativos (`../Cellar/...`) partem da pasta do link.
+        let target = match path.parent() {
+            Some(dir) => dir.join(target),
+            None => target,
+        };
+        let components: Vec<String> = target
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_lowercase())
+            .collect();
+        let owner = components.iter().find(|c| c.ends_with(".app"));
+        let from_homebrew = components.iter().any(|c| c == "cellar" || c == "caskroom");
+        match owner {
+            Some(owner) if *owner == bundle => linked.push(path),
+            None if !from_homebrew && name_matches(&name) => by_name.push(path),
+            _ => {}
+        }
+    }
+    linked.sort();
+    by_name.sort();
+    (linked, by_name)
+}
+
+/// Caminhos em /Library ou /System so podem ser removidos com `sudo`.
+pub fn requires_elevation(path: &Path) -> bool {
+    path.starts_with("/Library") || path.starts_with("/System")
+}
+
+/// Plugins `.mailbundle` em ~/Library/Mail/Bundles cujo nome contem o nome do app.
+pub fn find_mail_plugins(app_name: &str) -> Vec<PathBuf> {
+    find_mail_plugins_in(&get_home(), app_name)
+}
+
+fn find_mail_plugins_in(home: &Path, app_name: &str) -> Vec<PathBuf> {
+    find_entries_with_extension(&home.join("Library/Mail/Bundles"), "mailbundle", app_name)
+}
+
+/// Plugins `.bundle` em ~/Library/Application Support/AddressBook/Plug-Ins cujo
+/// nome contem o nome do app.
+pub fn find_contacts_plugins(app_name: &str) -> Vec<PathBuf> {
+    find_contacts_plugins_in(&get_home(), app_name)
+}
+
+fn find_contacts_plugins_in(home: &Path, app_name: &str) -> Vec<PathBuf> {
+    let dir = home.join("Library/Application Support/AddressBook/Plug-Ins");
+    find_entries_with_extension(&dir, "bundle", app_name)
+}
+
+/// Logs do app em ~/Library/Logs, em dois niveis. No primeiro, pastas so entram
+/// com o nome exato do app (ou comecando pelo bundle ID), inteiras; arquivos
+/// basta conter o nome. Dentro das outras pastas (`DiagnosticReports`,
+/// `com.apple.EmbeddedOSLog`...) entra tudo que contem o nome ou o bundle ID.
+pub fn find_app_logs(app_name: &str, bundle_id: Option<&str>) -> Vec<PathBuf> {
+    find_app_logs_in(&get_home(), app_name, bundle_id)
+}
+
+fn find_app_logs_in(home: &Path, app_name: &str, bundle_id: Option<&str>) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(home.join("Library/Logs")) else {
+        return Vec::new();
+    };
+    let mut found = Vec::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = entry.path();
+        if !path.is_dir() {
+            if MatchMode::SubstringAny.matches(&name, app_name, bundle_id) {
+                found.push(path);
+            }
+        } else if name.eq_ignore_ascii_case(app_name)
+            || MatchMode::PrefixBundleId.matches(&name, app_name, bundle_id)
+        {
+            found.push(path);
+        } else if let Ok(children) = fs::read_dir(&path) {
+            found.extend(
+                children
+                    .flatten()
+                    .filter(|c| {
+                        let child = c.file_name().to_string_lossy().to_string();
+                        MatchMode::SubstringAny.matches(&child, app_name, bundle_id)
+                    })
+                    .map(|c| c.path()),
+            );
+        }
+    }
+    found.sort();
+    found
+}
+
+/// Cookies do app em ~/Library/Cookies: `<bundle-id>.binarycookies` ou, se nao
+/// existir, `<app_name>.binarycookies`.
+pub fn find_cookie_store(app_name: &str, bundle_id: Option<&str>) -> Option<PathBuf> {
+    find_cookie_store_in(&get_home(), app_name, bundle_id)
+}
+
+fn find_cookie_store_in(home: &Path, app_name: &str, bundle_id: Option<&str>) -> Option<PathBuf> {
+    let dir = home.join("Library/Cookies");
+    bundle_id
+        .into_iter()
+        .chain(std::iter::once(app_name))
+        .map(|name| dir.join(format!("{}.binarycookies", name)))
+        .find(|path| path.is_file())
+}
+
+/// Hosts do `~/Library/Cookies/HSTS.plist` no dominio do app (deduzido do
+/// bundle ID: `com.example.app` -> `example.com`). O arquivo e compartilhado
+/// por todos os apps e nunca e alterado; as entradas so sao informadas.
+pub fn find_hsts_entries(bundle_id: &str) -> Vec<String> {
+    find_hsts_entries_in(&get_home(), bundle_id)
+}
+
+fn find_hsts_entries_in(home: &Path, bundle_id: &str) -> Vec<String> {
+    let Some(domain) = bundle_id_domain(bundle_id) else {
+        return Vec::new();
+    };
+    let mut hosts = read_plist_json(&home.join("Library/Cookies/HSTS.plist"))
+        .map(|hsts| hsts_hosts_in(&hsts, &domain))
+        .unwrap_or_default();
+    // Dominios como `google.com` ou `github.io` sao compartilhados por muitos apps:
+    // so contam os hosts que citam o terceiro rotulo do bundle ID.
+    if let Some(label) = bundle_id.split('.').nth(2).filter(|l| !l.is_empty()) {
+        let label = label.to_lowercase();
+        hosts.retain(|host| host.to_lowercase().contains(&label));
+    }
+    hosts
+}
+
+/// `com.example.app` -> `example.com`. Apps da Apple ficam de fora: o dominio
+/// `apple.com` nao identifica nenhum app.
+fn bundle_id_domain(bundle_id: &str) -> Option<String> {
+    let mut parts = bundle_id.split('.');
+    let (tld, name) = (parts.next()?, parts.next()?);
+    if tld.is_empty() || name.is_empty() || name.eq_ignore_ascii_case("apple") {
+        return None;
+    }
+    Some(format!("{}.{}", name, tld).to_lowercase())
+}
+
+/// Chaves de qualquer nivel do plist que sao `domain` ou um subdominio dele.
+fn hsts_hosts_in(hsts: &serde_json::Value, domain: &str) -> Vec<String> {
+    fn collect(value: &serde_json::Value, domain: &str, hosts: &mut Vec<String>) {
+        let Some(dict) = value.as_object() else {
+            return;
+        };
+        for (key, child) in dict {
+            let host = key.to_lowercase();
+            if host == domain || host.ends_with(&format!(".{}", domain)) {
+                hosts.push(key.clone());
+            }
+            collect(child, domain, hosts);
+        }
+    }
+    let mut hosts = Vec::new();
+    collect(hsts, domain, &mut hosts);
+    hosts.sort();
+    hosts.dedup();
+    hosts
+}
+
+/// Le um plist qualquer (binario ou XML) como JSON.
+#[cfg(feature = "native-plist")]
+fn read_plist_json(path: &Path) -> Option<serde_json::Value> {
+    serde_json::to_value(plist::Value::from_file(path).ok()?).ok()
+}
+
+#[cfg(not(feature = "native-plist"))]
+fn read_plist_json(path: &Path) -> Option<serde_json::Value> {
+    let output = Command::new("plutil")
+        .args(["-convert", "json", "-o", "-"])
+        .arg(path)
+        .output()
+        .ok()?;
+    serde_json::from_slice(&output.stdout).ok()
+}
+
+/// Capturas `.diag`/`.diagzip` em ~/Library/Diagnostics com o nome do app.
+fn find_diagnostics_in(home: &Path, app_name: &str) -> Vec<PathBuf> {
+    let dir = home.join("Library/Diagnostics");
+    let mut found = find_entries_with_extension(&dir, "diag", app_name);
+    found.extend(find_entries_with_extension(&dir, "diagzip", app_name));
+    found
+}
+
+/// Listas de itens recentes (`.sfl2`/`.sfl3`) em
+/// ~/Library/Application Support/com.apple.sharedfilelist que citam o bundle ID.
+pub fn find_sfl_files(bundle_id: &str) -> Vec<PathBuf> {
+    find_sfl_files_in(&get_home(), bundle_id)
+}
+
+fn find_sfl_files_in(home: &Path, bundle_id: &str) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    collect_sfl_files(
+        &home.join("Library/Application Support/com.apple.sharedfilelist"),
+        bundle_id.as_bytes(),
+        &mut found,
+    );
+    found.sort();
+    found
+}
+
+fn collect_sfl_files(dir: &Path, needle: &[u8], found: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            collect_sfl_files(&path, needle, found);
+            continue;
+        }
+        let is_sfl = matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("sfl2" | "sfl3")
+        );
+        // O nome do arquivo ja identifica as listas por app.
+        let named = path
+            .file_stem()
+            .is_some_and(|s| s.as_encoded_bytes() == needle);
+        let mentions =
+            || fs::read(&path).is_ok_and(|data| data.windows(needle.len()).any(|w| w == needle));
+        if is_sfl && !needle.is_empty() && (named || mentions()) {
+            found.push(path);
+        }
+    }
+}
+
+/// Entradas de `dir` com a extensao dada cujo nome contem `app_name`.
+fn find_entries_with_extension(dir: &Path, extension: &str, app_name: &str) -> Vec<PathBuf> {
+    let mut plugins = Vec::new();
+    let name_lower = app_name.to_lowercase();
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_bundle = path.extension().and_then(|e| e.to_str()) == Some(extension);
+            let entry_name = entry.file_name().to_string_lossy().to_lowercase();
+            if is_bundle && entry_name.contains(&name_lower) {
+                plugins.push(path);
+            }
+        }
+    }
+    plugins
+}
+
+/// Pede ao Mail.app para recarregar seus plugins apos a remocao de um `.mailbundle`.
+pub fn reload_mail_plugins() {
+    let _ = Command::new("killall").args(["-HUP", "Mail"]).output();
+}
+
+/// Reabre o Contatos, se estiver aberto, para descarregar plugins removidos.
+pub fn restart_contacts_if_running() {
+    let _ = Command::new("osascript")
+        .args([
+            "-e",
+            "if application \"Contacts\" is running then",
+            "-e",
+            "tell application \"Contacts\" to quit",
+            "-e",
+            "delay 1",
+            "-e",
+            "tell application \"Contacts\" to launch",
+            "-e",
+            "end if",
+        ])
+        .output();
+}
+
+/// Recarrega o Mail e/ou o Contatos se algum plugin deles estiver entre os removidos.
+pub fn reload_plugin_hosts<'a>(removed: impl IntoIterator<Item = &'a RelatedFile>) {
+    let (mut mail, mut contacts) = (false, false);
+    for file in removed {
+        mail |= file.category == RelatedFileCategory::MailPlugin;
+        contacts |= file.category == RelatedFileCategory::ContactsPlugin;
+    }
+    if mail {
+        reload_mail_plugins();
+    }
+    if contacts {
+        restart_contacts_if_running();
+    }
+}
+
+/// Registro do Background Task Management (Ventura+) listado por `sfltool dumpbtm`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BtmEntry {
+    pub name: String,
+    /// Ex: `4.com.example.app.helper` (o prefixo numerico e o tipo do item).
+    pub identifier: String,
+    pub bundle_id: Option<String>,
+    pub url: Option<String>,
+}
+
+impl BtmEntry {
+    fn belongs_to(&self, app_name: &str, bundle_id: Option<&str>) -> bool {
+        let by_id = bundle_id.is_some_and(|id| {
+            self.identifier.contains(id)
+                || self.bundle_id.as_deref().is_some_and(|b| b.starts_with(id))
+        });
+        let marker = format!("/{}.app/", app_name.replace(' ', "%20"));
+        by_id || self.url.as_deref().is_some_and(|url| url.contains(&marker))
+    }
+
+    /// O registro aponta para o proprio `.app`, nao para um helper dentro dele:
+    /// e o que a lista classica de itens de inicio mostra.
+    fn is_app_bundle(&self, app_name: &str) -> bool {
+        let marker = format!("/{}.app/", app_name.replace(' ', "%20"));
+        self.url
+            .as_deref()
+            .is_some_and(|url| url.ends_with(&marker))
+    }
+}
+
+/// O que `remove_login_items` conseguiu limpar.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoginItemsReport {
+    /// O item de inicio classico (System Events) foi removido.
+    pub removed_login_item: bool,
+    /// Itens do app tirados do `backgrounditems.btm` do usuario.
+    pub removed_btm: usize,
+    /// Registros do BTM que continuam: os do Ventura+ ficam num banco do
+    /// sistema, so alteravel como root, e o `sfltool` nao remove entradas
+    /// isoladas (so `resetbtm`, que apaga todas).
+    pub remaining_btm: Vec<BtmEntry>,
+}
+
+impl LoginItemsReport {
+    /// Aviso para o usuario sobre os registros que sobraram.
+    pub fn note(&self) -> Option<String> {
+        (!self.remaining_btm.is_empty()).then(|| {
+            format!(
+                "o macOS ainda lista {} item(ns) em segundo plano do app em Ajustes do \
+                 Sistema > Geral > Itens de Inicio; desative-os la (ou limpe todos com \
+                 `sudo sfltool resetbtm`).",
+                self.remaining_btm.len()
+            )
+        })
+    }
+}
+
+/// Itens de inicio do usuario, arquivados com `NSKeyedArchiver` pelo
+/// `backgroundtaskmanagementagent`.
+const BTM_ITEMS_FILE: &str =
+    "Library/Application Support/com.apple.backgroundtaskmanagementagent/backgrounditems.btm";
+
+/// Remove o item de inicio do app e os registros dele no `backgrounditems.btm`,
+/// editando o plist em vez de apagar o arquivo (que tem os itens dos outros
+/// apps). O System Events so e chamado se a lista classica tiver o app, para
+/// nao pedir permissao de automacao a toa.
+pub fn remove_login_items(app_name: &str, bundle_id: Option<&str>) -> LoginItemsReport {
+    let btm = get_home().join(BTM_ITEMS_FILE);
+    let entries = find_btm_entries(app_name, bundle_id);
+    let listed = entries.iter().any(|e| e.is_app_bundle(app_name))
+        || count_btm_items(&btm, app_name, bundle_id, false) > 0;
+    let removed_login_item = listed && delete_classic_login_item(app_name);
+    let removed_btm = count_btm_items(&btm, app_name, bundle_id, true);
+    if removed_btm > 0 {
+        // O agente guarda os itens em memoria; reiniciado, le o arquivo editado.
+        let _ = Command::new("killall")
+            .arg("backgroundtaskmanagementagent")
+            .output();
+    }
+    let remaining_btm = if removed_login_item || removed_btm > 0 {
+        find_btm_entries(app_name, bundle_id)
+    } else {
+        entries
+    };
+    LoginItemsReport {
+        removed_login_item,
+        removed_btm,
+        remaining_btm,
+    }
+}
+
+fn delete_classic_login_item(app_name: &str) -> bool {
+    let script = format!(
+        "tell application \"System Events\"\n\
+         if exists login item \"{0}\" then\n\
+         delete login item \"{0}\"\n\
+         return \"removido\"\n\
+         end if\n\
+         end tell",
+        app_name.replace('"', "")
+    );
+    Command::new("osascript")
+        .args(["-e", &script])
+        .output()
+        .is_ok_and(|o| String::from_utf8_lossy(&o.stdout).trim() == "removido")
+}
+
+/// Quantos itens do `.btm` citam o app; com `remove`, tira-os das listas e
+/// regrava o arquivo. Sem a feature `native-plist` o arquivo nao e lido.
+#[cfg(feature = "native-plist")]
+fn count_btm_items(btm: &Path, app_name: &str, bundle_id: Option<&str>, remove: bool) -> usize {
+    let Ok(mut value) = plist::Value::from_file(btm) else {
+        return 0;
+    };
+    let removed = remove_archived_items(&mut value, app_name, bundle_id);
+    if remove && removed > 0 && plist::to_file_binary(btm, &value).is_err() {
+        return 0;
+    }
+    removed
+}
+
+#[cfg(not(feature = "native-plist"))]
+fn count_btm_items(_btm: &Path, _app_name: &str, _bundle_id: Option<&str>, _remove: bool) -> usize {
+    0
+}
+
+/// Num arquivo do `NSKeyedArchiver` os objetos ficam em `$objects` e se citam
+/// por UID; cada lista (`NS.objects`) guarda os UIDs dos seus itens. Tira das
+/// listas os itens com um nome, URL ou bookmark que cite o app. Os objetos
+/// orfaos ficam em `$objects`, o que o `NSKeyedUnarchiver` aceita.
+#[cfg(feature = "native-plist")]
+fn remove_archived_items(
+    value: &mut plist::Value,
+    app_name: &str,
+    bundle_id: Option<&str>,
+) -> usize {
+    use plist::Value;
+
+    let bundle = format!("{}.app", app_name);
+    let mentions = |v: &Value| match v {
+        Value::String(s) => s == app_name || contains_name(s.as_bytes(), &bundle),
+        Value::Data(d) => contains_name(d, &bundle),
+        _ => false,
+    } || bundle_id.is_some_and(|id| match v {
+        Value::String(s) => s.contains(id),
+        Value::Data(d) => contains_name(d, id),
+        _ => false,
+    });
+    let Some(objects) = value
+        .as_dictionary_mut()
+        .and_then(|d| d.get_mut("$objects"))
+        .and_then(|o| o.as_array_mut())
+    else {
+        return 0;
+    };
+    let belongs = |v: &Value| {
+        let resolved = match v {
+            Value::Uid(uid) => objects.get(uid.get() as usize),
+            other => Some(other),
+        };
+        match resolved {
+            // `NSData` mutavel: os bytes ficam em `NS.data`.
+            Some(Value::Dictionary(d)) => d.get("NS.data").is_some_and(mentions),
+            Some(other) => mentions(other),
+            None => false,
+        }
+    };
+    let app_items: HashSet<u64> = objects
+        .iter()
+        .enumerate()
+        .filter(|(_, o)| {
+            o.as_dictionary()
+                .is_some_and(|d| !d.contains_key("NS.objects") && d.values().any(belongs))
+        })
+        .map(|(i, _)| i as u64)
+        .collect();
+    if app_items.is_empty() {
+        return 0;
+    }
+    let mut removed = 0;
+    for object in objects.iter_mut() {
+        let Some(list) = object
+            .as_dictionary_mut()
+            .and_then(|d| d.get_mut("NS.objects"))
+            .and_then(|l| l.as_array_mut())
+        else {
+            continue;
+        };
+        let before = list.len();
+        list.retain(|item| !matches!(item, Value::Uid(uid) if app_items.contains(&uid.get())));
+        removed += before - list.len();
+    }
+    removed
+}
+
+/// `name` em `bytes`, sem ser o fim de outro nome (`Foo.app` nao casa com
+/// `MyFoo.app`).
+#[cfg(feature = "native-plist")]
+fn contains_name(bytes: &[u8], name: &str) -> bool {
+    let name = name.as_bytes();
+    !name.is_empty()
+        && bytes
+            .windows(name.len())
+            .enumerate()
+            .any(|(i, w)| w == name && (i == 0 || !bytes[i - 1].is_ascii_alphanumeric()))
+}
+
+/// Registros do BTM do app. No Ventura+ o `sfltool dumpbtm` exige root; sem
+/// permissao a lista fica vazia.
+pub fn find_btm_entries(app_name: &str, bundle_id: Option<&str>) -> Vec<BtmEntry> {
+    let output = match Command::new("sfltool").arg("dumpbtm").output() {
+        Ok(o) if o.status.success() => o,
+        _ => return Vec::new(),
+    };
+    parse_btm_dump(&String::from_utf8_lossy(&output.stdout))
+        .into_iter()
+        .filter(|e| e.belongs_to(app_name, bundle_id))
+        .collect()
+}
+
+/// Cada item do `dumpbtm` comeca com ` #N:` seguido de linhas `Campo: valor`.
+fn parse_btm_dump(dump: &str) -> Vec<BtmEntry> {
+    let mut entries = Vec::new();
+    let mut current: Option<BtmEntry> = None;
+    for line in dump.lines().map(str::trim) {
+        if line.starts_with('#') && line.ends_with(':') {
+            entries.extend(current.take());
+            current = Some(BtmEntry::default());
+            continue;
+        }
+        let (Some(entry), Some((field, value))) = (current.as_mut(), line.split_once(": ")) else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        match field {
+            "Name" => entry.name = value,
+            "Identifier" => entry.identifier = value,
+            "Bundle Identifier" => entry.bundle_id = Some(value),
+            "URL" => entry.url = Some(value),
+            _ => {}
+        }
+    }
+    entries.extend(current);
+    entries
+}
+
+/// Remove da lista os arquivos que as opcoes pedem para manter.
+pub fn filter_related_files(related: Vec<RelatedFile>, opts: &RemovalOptions) -> Vec<RelatedFile> {
+    filter_related_files_by(related, |f| opts.should_remove(f))
+}
+
+/// Deixa na lista so os arquivos para os quais `should_remove` responde `true`
+/// (chamada uma vez por arquivo, na ordem). Uma entrada que contem outra
+/// mantida (ex: o container com o CloudKit desmarcado) tambem fica, porque
+/// remove-la apagaria a de dentro.
+pub fn filter_related_files_by(
+    related: Vec<RelatedFile>,
+    mut should_remove: impl FnMut(&RelatedFile) -> bool,
+) -> Vec<RelatedFile> {
+    let remove: Vec<bool> = related.iter().map(&mut should_remove).collect();
+    let kept: Vec<PathBuf> = related
+        .iter()
+        .zip(&remove)
+        .filter(|(_, remove)| !**remove)
+        .map(|(f, _)| f.path.clone())
+        .collect();
+    related
+        .into_iter()
+        .zip(remove)
+        .filter(|(f, remove)| *remove && !kept.iter().any(|k| k.starts_with(&f.path)))
+        .map(|(f, _)| f)
+        .collect()
+}
+
+pub fn is_app_running(app_name: &str) -> bool {
+    let marker = format!("{}.app/", app_name);
+    process_paths()
+        .iter()
+        .any(|(_, path)| path.to_string_lossy().contains(&marker))
+}
+
+/// Processos (PID, executavel) com um componente do caminho igual ao bundle ID
+/// ou comecando por `<bundle_id>.`, incluindo helpers XPC
+/// (`com.example.app.Helper`). `com.example.application` nao conta.
+pub fn get_app_processes(bundle_id: &str) -> Vec<(libc::pid_t, PathBuf)> {
+    processes_matching(process_paths(), bundle_id)
+}
+
+fn processes_matching(
+    processes: Vec<(libc::pid_t, PathBuf)>,
+    bundle_id: &str,
+) -> Vec<(libc::pid_t, PathBuf)> {
+    let prefix = format!("{}.", bundle_id);
+    processes
+        .into_iter()
+        .filter(|(_, path)| {
+            path.components().any(|c| {
+                let name = c.as_os_str().to_string_lossy();
+                name == bundle_id || name.starts_with(&prefix)
+            })
+        })
+        .collect()
+}
+
+/// PID e executavel de todos os processos visiveis, lidos direto da libproc
+/// (`proc_listpids` + `proc_pidpath`) sem criar um processo `ps` ou `pgrep`.
+#[cfg(target_os = "macos")]
+fn process_paths() -> Vec<(libc::pid_t, PathBuf)> {
+    use std::ffi::OsStr;
+    use std::mem::size_of;
+    use std::os::unix::ffi::OsStrExt;
+
+    const PROC_ALL_PIDS: u32 = 1;
+    const PID_SIZE: usize = size_of::<libc::pid_t>();
+
+    // Com buffer nulo, `proc_listpids` retorna so o tamanho necessario em bytes.
+    let needed = unsafe { libc::proc_listpids(PROC_ALL_PIDS, 0, std::ptr::null_mut(), 0) };
+    if needed <= 0 {
+        return Vec::new();
+    }
+    // Folga para processos criados entre as duas chamadas.
+    let mut pids: Vec<libc::pid_t> = vec![0; needed as usize / PID_SIZE + 64];
+    let filled = unsafe {
+        libc::proc_listpids(
+            PROC_ALL_PIDS,
+            0,
+            pids.as_mut_ptr().cast(),
+            (pids.len() * PID_SIZE) as libc::c_int,
+        )
+    };
+    if filled <= 0 {
+        return Vec::new();
+    }
+    pids.truncate(filled as usize / PID_SIZE);
+
+    let mut buf = vec![0u8; libc::PROC_PIDPATHINFO_MAXSIZE as usize];
+    pids.into_iter()
+        .filter(|&pid| pid > 0)
+        .filter_map(|pid| {
+            let len = unsafe { libc::proc_pidpath(pid, buf.as_mut_ptr().cast(), buf.len() as u32) };
+            (len > 0).then(|| (pid, PathBuf::from(OsStr::from_bytes(&buf[..len as usize]))))
+        })
+        .collect()
+}
+
+/// Fora do macOS nao ha libproc; usa a saida do `ps`.
+#[cfg(not(target_os = "macos"))]
+fn process_paths() -> Vec<(libc::pid_t, PathBuf)> {
+    match Command::new("ps").args(["-ax", "-o", "pid,comm"]).output() {
+        Ok(o) if o.status.success() => parse_ps_output(&String::from_utf8_lossy(&o.stdout)),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(any(not(target_os = "macos"), test))]
+fn parse_ps_output(output: &str) -> Vec<(libc::pid_t, PathBuf)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (pid, comm) = line.trim().split_once(char::is_whitespace)?;
+            Some((pid.parse().ok()?, PathBuf::from(comm.trim())))
+        })
+        .collect()
+}
+
+/// Quanto `force_quit_app` espera os processos fecharem depois do SIGTERM.
+const FORCE_QUIT_GRACE: Duration = Duration::from_secs(3);
+
+/// Encerra os processos do app (ver `get_app_processes`), incluindo helpers:
+/// primeiro com SIGTERM e, depois de `FORCE_QUIT_GRACE`, com SIGKILL so os que
+/// continuarem abertos.
+pub fn force_quit_app(bundle_id: &str) {
+    let pids: Vec<libc::pid_t> = get_app_processes(bundle_id)
+        .into_iter()
+        .map(|(pid, _)| pid)
+        .collect();
+    for &pid in &pids {
+        unsafe {
+            libc::kill(pid, libc::SIGTERM);
+        }
+    }
+    let deadline = Instant::now() + FORCE_QUIT_GRACE;
+    let mut remaining = pids;
+    while !remaining.is_empty() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(100));
+        // Relido a cada volta: um PID que sumiu pode ter sido reusado por outro processo.
+        let alive = get_app_processes(bundle_id);
+        remaining.retain(|pid| alive.iter().any(|(p, _)| p == pid));
+    }
+    for pid in remaining {
+        unsafe {
+            libc::kill(pid, libc::SIGKILL);
+        }
+    }
+}
+
+pub fn quit_app(app_name: &str) {
+    let _ = Command::new("osascript")
+        .args(["-e", &format!("tell application \"{}\" to quit", app_name)])
+        .output();
+}
+
+/// Apaga `path`. Se faltar permissao, tira os atributos estendidos restritos
+/// (ver `clear_restricted_xattrs`) e tenta mais uma vez.
+pub fn remove_path(path: &Path) -> io::Result<()> {
+    match remove_path_once(path) {
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+            if clear_restricted_xattrs(path).is_err() {
+                return Err(e);
+            }
+            remove_path_once(path)
+        }
+        result => result,
+    }
+}
+
+fn remove_path_once(path: &Path) -> io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Atributos estendidos que impedem a remocao mesmo pelo dono do arquivo.
+const RESTRICTED_XATTRS: &[&str] = &["com.apple.rootless", "com.apple.system.security"];
+
+/// Remove de `path` (e de tudo dentro dele) os atributos de `RESTRICTED_XATTRS`.
+/// `com.apple.rootless` so sai com o SIP desativado; nesse caso o erro do
+/// `removexattr` e retornado.
+pub fn clear_restricted_xattrs(path: &Path) -> Result<(), RemoverError> {
+    Ok(clear_restricted_xattrs_in_tree(path)?)
+}
+
+fn clear_restricted_xattrs_in_tree(path: &Path) -> io::Result<()> {
+    let mut first_error = None;
+    for name in xattr_names(path)? {
+        if RESTRICTED_XATTRS.contains(&name.as_str()) {
+            log_at!(debug, "removendo atributo {} de {}", name, path.display());
+            if let Err(e) = remove_xattr(path, &name) {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    if fs::symlink_metadata(path)?.is_dir() {
+        for entry in fs::read_dir(path)?.flatten() {
+            if let Err(e) = clear_restricted_xattrs_in_tree(&entry.path()) {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    first_error.map_or(Ok(()), Err)
+}
+
+#[cfg(target_os = "macos")]
+fn c_path(path: &Path) -> io::Result<std::ffi::CString> {
+    use std::os::unix::ffi::OsStrExt;
+    std::ffi::CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+/// Nomes dos atributos estendidos de `path`, sem seguir symlinks.
+#[cfg(target_os = "macos")]
+fn xattr_names(path: &Path) -> io::Result<Vec<String>> {
+    let path = c_path(path)?;
+    // Com buffer nulo, `listxattr` retorna so o tamanho necessario.
+    let needed =
+        unsafe { libc::listxattr(path.as_ptr(), std::ptr::null_mut(), 0, libc::XATTR_NOFOLLOW) };
+    if needed < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let mut buf = vec![0u8; needed as usize];
+    let len = unsafe {
+        libc::listxattr(
+            path.as_ptr(),
+            buf.as_mut_ptr().cast(),
+            buf.len(),
+            libc::XATTR_NOFOLLOW,
+        )
+    };
+    if len < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    buf.truncate(len as usize);
+    Ok(split_xattr_names(&buf))
+}
+
+#[cfg(target_os = "macos")]
+fn remove_xattr(path: &Path, name: &str) -> io::Result<()> {
+    let path = c_path(path)?;
+    let name =
+        std::ffi::CString::new(name).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let status = unsafe { libc::removexattr(path.as_ptr(), name.as_ptr(), libc::XATTR_NOFOLLOW) };
+    if status < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Fora do macOS os atributos `com.apple.*` nao existem.
+#[cfg(not(target_os = "macos"))]
+fn xattr_names(_path: &Path) -> io::Result<Vec<String>> {
+    Ok(Vec::new())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn remove_xattr(_path: &Path, _name: &str) -> io::Result<()> {
+    Ok(())
+}
+
+/// A lista do `listxattr` e uma sequencia de nomes terminados em `\0`.
+#[cfg(any(target_os = "macos", test))]
+fn split_xattr_names(buf: &[u8]) -> Vec<String> {
+    buf.split(|&b| b == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+        .collect()
+}
+
+/// Remove `path` apagando de vez ou movendo para a Lixeira, conforme `strategy`.
+pub fn remove_path_safely(path: &Path, strategy: RemovalStrategy) -> Result<(), RemoverError> {
+    remove_with_strategy(path, strategy)?;
+    Ok(())
+}
+
+/// Com `MoveToTrash`, retorna onde o item foi parar na Lixeira.
+fn remove_with_strategy(path: &Path, strategy: RemovalStrategy) -> io::Result<Option<PathBuf>> {
+    match strategy {
+        RemovalStrategy::PermanentDelete => remove_path(path).map(|_| None),
+        RemovalStrategy::MoveToTrash => move_to_trash(path).map(Some),
+    }
+}
+
+/// Move `path` para `~/.Trash` e retorna o novo caminho.
+pub fn move_to_trash(path: &Path) -> io::Result<PathBuf> {
+    move_to_trash_in(path, &get_home().join(".Trash"))
+}
+
+fn move_to_trash_in(path: &Path, trash: &Path) -> io::Result<PathBuf> {
+    move_to_trash_with(path, trash, |from, to| fs::rename(from, to))
+}
+
+/// Apps em outro volume e itens de /Library nao podem ir para `~/.Trash` com
+/// `rename` (`EXDEV`); nesse caso copia e apaga o original.
+fn move_to_trash_with(
+    path: &Path,
+    trash: &Path,
+    rename: impl Fn(&Path, &Path) -> io::Result<()>,
+) -> io::Result<PathBuf> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "caminho sem nome"))?;
+    fs::create_dir_all(trash)?;
+    let dest = trash_destination(trash, Path::new(name));
+    match rename(path, &dest) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            if let Err(e) = copy_tree(path, &dest) {
+                let _ = remove_path_once(&dest);
+                return Err(e);
+            }
+            remove_path(path)?;
+        }
+        result => result?,
+    }
+    Ok(dest)
+}
+
+/// Copia `from` para `to` recriando symlinks em vez de segui-los.
+fn copy_tree(from: &Path, to: &Path) -> io::Result<()> {
+    let meta = fs::symlink_metadata(from)?;
+    if meta.file_type().is_symlink() {
+        std::os::unix::fs::symlink(fs::read_link(from)?, to)
+    } else if meta.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_tree(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        fs::set_permissions(to, meta.permissions())
+    } else {
+        fs::copy(from, to).map(|_| ())
+    }
+}
+
+/// Primeiro nome livre na Lixeira, numerando como o Finder:
+/// `Foo.app`, `Foo 2.app`, `Foo 3.app`...
+fn trash_destination(trash: &Path, name: &Path) -> PathBuf {
+    let dest = trash.join(name);
+    if fs::symlink_metadata(&dest).is_err() {
+        return dest;
+    }
+    (2..)
+        .map(|n| trash.join(numbered_copy_name(name, n)))
+        .find(|candidate| fs::symlink_metadata(candidate).is_err())
+        .unwrap()
+}
+
+/// `Foo.app` -> `Foo <n>.app`.
+fn numbered_copy_name(name: &Path, n: usize) -> String {
+    let stem = name.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = name
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    format!("{} {}{}", stem, n, ext)
+}
+
+/// Igual a `remove_path`, mas tenta de novo (ate `max_attempts` vezes no total)
+/// quando o arquivo esta ocupado (`EBUSY`), como acontece quando o Finder
+/// ainda mantem o arquivo aberto.
+pub fn remove_path_with_retry(
+    path: &Path,
+    max_attempts: usize,
+    delay: Duration,
+) -> Result<(), RemoverError> {
+    retry_while_busy(path, max_attempts, delay, RemovalStrategy::PermanentDelete)?;
+    Ok(())
+}
+
+/// `remove_path_with_retry` com a estrategia e as tentativas de `opts`. Retorna
+/// o caminho na Lixeira, se o item foi movido para la.
+fn remove_with_options(
+    path: &Path,
+    opts: &RemovalOptions,
+) -> Result<Option<PathBuf>, RemoverError> {
+    retry_while_busy(path, opts.retry_attempts, opts.retry_delay, opts.strategy)
+}
+
+fn retry_while_busy(
+    path: &Path,
+    max_attempts: usize,
+    delay: Duration,
+    strategy: RemovalStrategy,
+) -> Result<Option<PathBuf>, RemoverError> {
+    let mut attempt = 1;
+    loop {
+        match remove_with_strategy(path, strategy) {
+            Ok(trashed) => return Ok(trashed),
+            Err(e) if attempt < max_attempts && is_busy(&e) => {
+                log_at!(debug, "{} ocupado ({}), nova tentativa", path.display(), e);
+                attempt += 1;
+                thread::sleep(delay);
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+fn is_busy(e: &io::Error) -> bool {
+    e.kind() == io::ErrorKind::Other || e.raw_os_error() == Some(libc::EBUSY)
+}
+
+/// Localiza o app e monta o plano de remocao, sem alterar o sistema de arquivos.
+pub fn plan_removal(app_name: &str, opts: &RemovalOptions) -> Result<RemovalPlan, RemoverError> {
+    let app_path =
+        find_app(app_name).ok_or_else(|| RemoverError::AppNotFound(app_name.to_string()))?;
+    plan_removal_at(&app_path, opts, &Config::default())
+}
+
+/// Igual a `plan_removal`, para um bundle ja localizado. Sem `config.team_id`,
+/// o Team ID e lido da assinatura do app.
+pub fn plan_removal_at(
+    app_path: &Path,
+    opts: &RemovalOptions,
+    config: &Config,
+) -> Result<RemovalPlan, RemoverError> {
+    if !app_path.exists() {
+        return Err(RemoverError::AppNotFound(app_path.display().to_string()));
+    }
+    let bundle_id = get_bundle_id(app_path);
+    let app_stem = app_path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let related = plan_related_files_in(
+        &get_home(),
+        app_path,
+        &app_stem,
+        bundle_id.as_deref(),
+        opts,
+        config,
+    );
+
+    let app_size = dir_size(app_path).unwrap_or(0);
+    let total_bytes = app_size + related.iter().map(|f| f.size).sum::<u64>();
+    let mut warnings = Vec::new();
+    if is_app_running(&app_stem) {
+        warnings.push(Warning::AppIsRunning);
+    }
+    let processes = bundle_id
+        .as_deref()
+        .map(get_app_processes)
+        .unwrap_or_default();
+    if !processes.is_empty() {
+        let processes = processes.into_iter().map(|(_, path)| path).collect();
+        warnings.push(Warning::HelperProcessesRunning { processes });
+    }
+    warnings.extend(plan_warnings_in(
+        app_path,
+        bundle_id.as_deref(),
+        &get_home(),
+        &[
+            Path::new("/opt/homebrew/Caskroom"),
+            Path::new("/usr/local/Caskroom"),
+        ],
+        Path::new("/Library/PrivilegedHelperTools"),
+        &cli_tool_dirs(),
+    ));
+    Ok(RemovalPlan {
+        app_path: app_path.to_path_buf(),
+        bundle_id,
+        app_size,
+        related,
+        total_bytes,
+        warnings,
+    })
+}
+
+/// Arquivos residuais que `plan_removal_at` poe no plano, ja filtrados pelas
+/// opcoes e do maior para o menor. `estimate_disk_savings` usa a mesma lista,
+/// para a estimativa bater com o que sera removido.
+fn plan_related_files_in(
+    home: &Path,
+    app_path: &Path,
+    app_stem: &str,
+    bundle_id: Option<&str>,
+    opts: &RemovalOptions,
+    config: &Config,
+) -> Vec<RelatedFile> {
+    let config = Config {
+        team_id: config.team_id.clone().or_else(|| get_app_team_id(app_path)),
+        search_system_library: config.search_system_library || opts.include_system_library,
+        ..config.clone()
+    };
+    let mut found = find_related_files_in(home, app_stem, bundle_id, &config);
+    for path in find_installed_cli_tools(app_stem, bundle_id.unwrap_or_default()) {
+        found.push(RelatedFile {
+            size: fs::symlink_metadata(&path).map(|m| m.len()).unwrap_or(0),
+            category: RelatedFileCategory::HelperTool,
+            requires_elevation: !can_delete(&path),
+            contents: Vec::new(),
+            path,
+        });
+    }
+    let mut related = filter_related_files(found, opts);
+    sort_by_size_desc(&mut related);
+    related
+}
+
+/// Avisos que dependem so do disco (tudo menos `AppIsRunning`).
+fn plan_warnings_in(
+    app_path: &Path,
+    bundle_id: Option<&str>,
+    home: &Path,
+    caskrooms: &[&Path],
+    helper_tools: &Path,
+    cli_dirs: &[PathBuf],
+) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    if is_system_app(app_path) {
+        warnings.push(Warning::SystemProtected);
+    }
+    if is_mas_app(app_path) {
+        warnings.push(Warning::MasApp);
+    }
+    if let Some(cask) = homebrew_cask_in(app_path, caskrooms) {
+        warnings.push(Warning::HomebrewManaged { cask });
+    }
+    if has_entries(&app_path.join("Contents/Library/SystemExtensions")) {
+        warnings.push(Warning::SystemExtensionPresent);
+    }
+    if let Some(bid) = bundle_id {
+        let helper_installed = fs::read_dir(helper_tools)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .any(|e| e.file_name().to_string_lossy().starts_with(bid));
+        if helper_installed || has_entries(&app_path.join("Contents/Library/LaunchServices")) {
+            warnings.push(Warning::PrivilegedHelperPresent);
+        }
+        // iCloud Drive guarda cada container como `iCloud~com~vendor~app` (ou com o team ID).
+        let container = bid.replace('.', "~").to_lowercase();
+        let icloud = fs::read_dir(home.join("Library/Mobile Documents"))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .any(|e| {
+                let name = e.file_name().to_string_lossy().to_lowercase();
+                name.split_once('~').map(|(_, rest)| rest) == Some(container.as_str())
+            });
+        if icloud {
+            warnings.push(Warning::ICloudDataPresent);
+        }
+        if find_cloudkit_data_in(home, bid).is_some() {
+            warnings.push(Warning::CloudKitDataPresent);
+        }
+        let hosts = find_hsts_entries_in(home, bid);
+        if !hosts.is_empty() {
+            warnings.push(Warning::HstsEntries { hosts });
+        }
+    }
+    let app_stem = app_path.file_stem().unwrap_or_default().to_string_lossy();
+    let (_, paths) =
+        find_installed_cli_tools_in(cli_dirs, &app_stem, bundle_id.unwrap_or_default());
+    if !paths.is_empty() {
+        warnings.push(Warning::CliToolNameMatch { paths });
+    }
+    warnings
+}
+
+fn has_entries(dir: &Path) -> bool {
+    fs::read_dir(dir)
+        .map(|mut d| d.next().is_some())
+        .unwrap_or(false)
+}
+
+/// Nome do cask do Homebrew que instalou o app, se houver. O cask move o bundle
+/// para /Applications (ou deixa um symlink), mas o nome do cask costuma ser o
+/// nome do app em minusculas com hifens no lugar dos espacos.
+fn homebrew_cask_in(app_path: &Path, caskrooms: &[&Path]) -> Option<String> {
+    let real = fs::canonicalize(app_path).unwrap_or_else(|_| app_path.to_path_buf());
+    for caskroom in caskrooms {
+        if let Ok(rest) = real.strip_prefix(caskroom) {
+            if let Some(cask) = rest.components().next() {
+                return Some(cask.as_os_str().to_string_lossy().to_string());
+            }
+        }
+    }
+    let stem = app_path.file_stem()?.to_string_lossy().to_lowercase();
+    let token = stem.split_whitespace().collect::<Vec<_>>().join("-");
+    caskrooms
+        .iter()
+        .find(|caskroom| caskroom.join(&token).is_dir())
+        .map(|_| token)
+}
+
+/// Resultado de `check_removal_permissions`: cada caminho do plano cai em
+/// exatamente uma lista.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PermissionReport {
+    pub writable: Vec<PathBuf>,
+    /// O usuario atual nao pode apagar (pasta pai ou o proprio diretorio sem escrita).
+    pub read_only: Vec<PathBuf>,
+    /// Ja nao existe (nada a fazer).
+    pub missing: Vec<PathBuf>,
+    /// Em /Library ou /System: so com `sudo`.
+    pub requires_sudo: Vec<PathBuf>,
+}
+
+impl PermissionReport {
+    /// Algum caminho vai falhar se a remocao rodar sem privilegios.
+    pub fn has_problems(&self) -> bool {
+        !self.read_only.is_empty() || !self.requires_sudo.is_empty()
+    }
+}
+
+/// Confere, sem remover nada, se cada caminho do plano pode ser apagado pelo
+/// usuario atual, para mostrar os problemas antes de uma remocao demorada.
+pub fn check_removal_permissions(plan: &RemovalPlan) -> PermissionReport {
+    let mut report = PermissionReport::default();
+    let paths = std::iter::once(&plan.app_path).chain(plan.related.iter().map(|f| &f.path));
+    for path in paths {
+        // Antes do `missing`: sem privilegios, nem sempre da para ver o que ha em /Library.
+        let list = if requires_elevation(path) {
+            &mut report.requires_sudo
+        } else if fs::symlink_metadata(path).is_err() {
+            &mut report.missing
+        } else if can_delete(path) {
+            &mut report.writable
+        } else {
+            &mut report.read_only
+        };
+        list.push(path.clone());
+    }
+    report
+}
+
+/// Apagar exige escrita na pasta pai e, para diretorios, no proprio diretorio
+/// (para esvazia-lo). Nao desce nas subpastas.
+fn can_delete(path: &Path) -> bool {
+    let writable = |p: &Path| {
+        use std::os::unix::ffi::OsStrExt;
+        std::ffi::CString::new(p.as_os_str().as_bytes())
+            .is_ok_and(|c| unsafe { libc::access(c.as_ptr(), libc::W_OK) } == 0)
+    };
+    let parent_ok = path.parent().is_none_or(writable);
+    let is_dir = fs::symlink_metadata(path).is_ok_and(|m| m.is_dir());
+    parent_ok && (!is_dir || writable(path))
+}
+
+/// Eventos emitidos por `batch_remove_apps`.
+#[derive(Debug, Clone)]
+pub enum BatchProgress {
+    StartingApp {
+        name: String,
+        index: usize,
+        total: usize,
+    },
+    AppDone {
+        name: String,
+        result: RemovalResult,
+    },
+    /// Etapa de remocao que vale para o lote todo (ex: `RebuildingLaunchServices`).
+    Removal(RemovalProgress),
+    AllDone,
+}
+
+/// Planeja e remove cada app, um de cada vez. Retorna um resultado por nome,
+/// na mesma ordem; um app que nao pode ser planejado ou removido (ex: nao
+/// encontrado) vira um resultado com o erro em `errors`, sem interromper os demais.
+pub fn batch_remove_apps(
+    app_names: &[&str],
+    opts: &RemovalOptions,
+    progress: impl Fn(BatchProgress),
+) -> Vec<RemovalResult> {
+    let total = app_names.len();
+    let mut results = Vec::with_capacity(total);
+    // O banco do Launch Services e reconstruido uma vez so, depois do ultimo app.
+    let app_opts = RemovalOptions {
+        rebuild_launch_services: false,
+        ..opts.clone()
+    };
+    let mut installed = installed_bundle_ids_in(&app_dirs());
+    for (index, &name) in app_names.iter().enumerate() {
+        progress(BatchProgress::StartingApp {
+            name: name.to_string(),
+            index,
+            total,
+        });
+        let result = plan_removal(name, opts)
+            .and_then(|plan| execute_plan_with(&plan, &app_opts, &installed, |_| {}))
+            .unwrap_or_else(|e| RemovalResult {
+                app_path: PathBuf::from(name),
+                errors: vec![(PathBuf::from(name), e.to_string())],
+                ..RemovalResult::default()
+            });
+        installed.retain(|(path, _)| !result.removed.contains(path));
+        progress(BatchProgress::AppDone {
+            name: name.to_string(),
+            result: result.clone(),
+        });
+        results.push(result);
+    }
+    if opts.rebuild_launch_services && results.iter().any(|r| r.launch_services_handlers > 0) {
+        progress(BatchProgress::Removal(
+            RemovalProgress::RebuildingLaunchServices,
+        ));
+        rebuild_launch_services();
+    }
+    progress(BatchProgress::AllDone);
+    results
+}
+
+/// Executa um plano de `plan_removal`: fecha o app (e helpers que sobrarem),
+/// remove o bundle e os arquivos residuais e recarrega Mail/Contatos se algum
+/// plugin deles foi removido. Falhas em arquivos individuais ficam em
+/// `RemovalResult::errors`; apps de sistema sao recusados.
+pub fn execute_plan(
+    plan: &RemovalPlan,
+    opts: &RemovalOptions,
+    progress: impl Fn(RemovalProgress),
+) -> Result<RemovalResult, RemoverError> {
+    let installed = match plan.bundle_id {
+        Some(_) => installed_bundle_ids_in(&app_dirs()),
+        None => Vec::new(),
+    };
+    execute_plan_with(plan, opts, &installed, progress)
+}
+
+/// Igual a `execute_plan`, com os apps instalados (ver `installed_bundle_ids_in`)
+/// ja lidos, para `batch_remove_apps` ler uma vez so.
+fn execute_plan_with(
+    plan: &RemovalPlan,
+    opts: &RemovalOptions,
+    installed: &[(PathBuf, String)],
+    progress: impl Fn(RemovalProgress),
+) -> Result<RemovalResult, RemoverError> {
+    if is_system_app(&plan.app_path) {
+        return Err(RemoverError::SystemApp(plan.app_path.clone()));
+    }
+    let app_stem = plan
+        .app_path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    if is_app_running(&app_stem) {
+        progress(RemovalProgress::QuittingApp);
+        quit_app(&app_stem);
+        thread::sleep(Duration::from_secs(2));
+    }
+    if let Some(bid) = &plan.bundle_id {
+        let remaining = get_app_processes(bid);
+        if !remaining.is_empty() {
+            progress(RemovalProgress::ForceQuitting {
+                processes: remaining.len(),
+            });
+            force_quit_app(bid);
+        }
+    }
+
+    let mut result = RemovalResult {
+        app_path: plan.app_path.clone(),
+        ..Default::default()
+    };
+    // Lido antes de o bundle ser apagado.
+    let registers_handlers = !get_app_url_schemes(&plan.app_path).is_empty()
+        || !get_app_document_types(&plan.app_path).is_empty();
+    let other_copy_installed = plan
+        .bundle_id
+        .as_deref()
+        .is_some_and(|id| has_other_copy_installed(installed, &plan.app_path, id));
+    let mut removed_files = Vec::new();
+    let targets = std::iter::once((&plan.app_path, None)).chain(
+        removal_order(&plan.related)
+            .into_iter()
+            .map(|f| (&f.path, Some(f))),
+    );
+    let total = plan.related.len() + 1;
+    for (index, (path, file)) in targets.enumerate() {
+        progress(RemovalProgress::Removing {
+            path: path.clone(),
+            index,
+            total,
+        });
+        match remove_with_options(path, opts) {
+            Ok(trashed) => {
+                progress(RemovalProgress::Removed { path: path.clone() });
+                result.removed.push(path.clone());
+                result
+                    .trashed
+                    .extend(trashed.map(|dest| (path.clone(), dest)));
+                removed_files.extend(file);
+            }
+            Err(e) => {
+                progress(RemovalProgress::Failed {
+                    path: path.clone(),
+                    error: e.to_string(),
+                });
+                result.errors.push((path.clone(), e.to_string()));
+            }
+        }
+    }
+
+    let removed_category = |c| removed_files.iter().any(|f| f.category == c);
+    if removed_category(RelatedFileCategory::MailPlugin) {
+        progress(RemovalProgress::ReloadingPlugins { host: "Mail" });
+        reload_mail_plugins();
+    }
+    if removed_category(RelatedFileCategory::ContactsPlugin) {
+        progress(RemovalProgress::ReloadingPlugins { host: "Contatos" });
+        restart_contacts_if_running();
+    }
+    if let Some(id) = plan.bundle_id.as_deref() {
+        if needs_window_restoration_cleared(id, &removed_files) {
+            clear_window_restoration(id);
+        }
+    }
+    result.notes = removal_notes(removed_files.iter().copied())
+        .into_iter()
+        .map(String::from)
+        .collect();
+    if result.removed.contains(&plan.app_path) {
+        let login_items = remove_login_items(&app_stem, plan.bundle_id.as_deref());
+        result.notes.extend(login_items.note());
+        if registers_handlers {
+            unregister_app_handlers(&plan.app_path);
+        }
+        if !other_copy_installed {
+            result.launch_services_handlers =
+                unregister_launch_services(&plan.app_path, plan.bundle_id.as_deref());
+        }
+        if opts.rebuild_launch_services && result.launch_services_handlers > 0 {
+            progress(RemovalProgress::RebuildingLaunchServices);
+            rebuild_launch_services();
+        }
+    }
+    Ok(result)
+}
+
+/// Remove o app apos confirmacao e pergunta individualmente por cada arquivo residual.
+pub fn remove_app_safe_mode(app_name: &str) -> Result<RemovalResult, RemoverError> {
+    remove_app_safe_mode_with(app_name, &mut StdinPrompt)
+}
+
+/// Igual a `remove_app_safe_mode`, mas com uma fonte de confirmacoes customizada.
+pub fn remove_app_safe_mode_with<P: SafeModePrompt>(
+    app_name: &str,
+    prompt: &mut P,
+) -> Result<RemovalResult, RemoverError> {
+    let app_path =
+        find_app(app_name).ok_or_else(|| RemoverError::AppNotFound(app_name.to_string()))?;
+    if is_system_app(&app_path) {
+        return Err(RemoverError::SystemApp(app_path));
+    }
+    let bundle_id = get_bundle_id(&app_path);
+    let app_stem = app_path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let config = Config {
+        team_id: get_app_team_id(&app_path),
+        ..Default::default()
+    };
+    let related = find_related_files_with_config(&app_stem, bundle_id.as_deref(), &config);
+    remove_with_prompt(&app_path, &related, prompt)
+}
+
+/// Parte interativa do modo seguro: confirma o bundle e pergunta por cada um
+/// dos `related`, na ordem.
+fn remove_with_prompt<P: SafeModePrompt>(
+    app_path: &Path,
+    related: &[RelatedFile],
+    prompt: &mut P,
+) -> Result<RemovalResult, RemoverError> {
+    let app_size = dir_size(app_path).unwrap_or(0);
+    if !prompt.confirm_app(app_path, app_size) {
+        return Err(RemoverError::Cancelled);
+    }
+
+    let app_stem = app_path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    if is_app_running(&app_stem) {
+        quit_app(&app_stem);
+        thread::sleep(Duration::from_secs(2));
+    }
+
+    let opts = RemovalOptions::default();
+    let remove = |path: &Path| remove_with_options(path, &opts);
+    let mut result = RemovalResult {
+        app_path: app_path.to_path_buf(),
+        ..Default::default()
+    };
+    match remove(app_path) {
+        Ok(_) => result.removed.push(app_path.to_path_buf()),
+        Err(e) => result.errors.push((app_path.to_path_buf(), e.to_string())),
+    }
+
+    let mut removed_files = Vec::new();
+    let ordered = removal_order(related);
+    for (i, file) in ordered.iter().copied().enumerate() {
+        let path = &file.path;
+        // Remover a pasta levaria junto o que o usuario pediu para manter.
+        if result.skipped.iter().any(|kept| kept.starts_with(path)) {
+            result.skipped.push(path.clone());
+            continue;
+        }
+        match prompt.ask_file(path, file.size) {
+            SafeModeChoice::Remove => match remove(path) {
+                Ok(_) => {
+                    removed_files.push(file);
+                    result.removed.push(path.clone());
+                }
+                Err(e) => result.errors.push((path.clone(), e.to_string())),
+            },
+            SafeModeChoice::Skip => result.skipped.push(path.clone()),
+            SafeModeChoice::Quit => {
+                result
+                    .skipped
+                    .extend(ordered[i..].iter().map(|f| f.path.clone()));
+                break;
+            }
+        }
+    }
+
+    reload_plugin_hosts(removed_files.iter().copied());
+    result.notes = removal_notes(removed_files.iter().copied())
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+    Ok(result)
+}
+
+/// Tamanho total de um arquivo ou diretorio. Entradas sem permissao de leitura
+/// sao ignoradas (e registradas com `log::warn!` na feature `log`); so retorna
+/// erro se o proprio `path` nao puder ser lido.
+pub fn dir_size(path: &Path) -> io::Result<u64> {
+    let mut total: u64 = 0;
+    if path.is_file() {
+        return Ok(fs::metadata(path)?.len());
+    }
+    for entry in fs::read_dir(path)? {
+        let entry = match entry {
+            Ok(e) => e,
+            Err(_e) => {
+                #[cfg(feature = "log")]
+                log::warn!("dir_size: entrada ilegivel em {}: {}", path.display(), _e);
+                continue;
+            }
+        };
+        let meta = match entry.metadata() {
+            Ok(m) => m,
+            Err(_e) => {
+                #[cfg(feature = "log")]
+                log::warn!("dir_size: ignorando {}: {}", entry.path().display(), _e);
+                continue;
+            }
+        };
+        if meta.is_dir() {
+            match dir_size(&entry.path()) {
+                Ok(size) => total += size,
+                Err(_e) => {
+                    #[cfg(feature = "log")]
+                    log::warn!("dir_size: ignorando {}: {}", entry.path().display(), _e);
+                }
+            }
+        } else {
+            total += meta.len();
+        }
+    }
+    Ok(total)
+}
+
+/// Igual a `dir_size`, mas falha no primeiro erro de leitura em qualquer nivel.
+pub fn dir_size_strict(path: &Path) -> io::Result<u64> {
+    let mut total: u64 = 0;
+    if path.is_file() {
+        return Ok(fs::metadata(path)?.len());
+    }
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let meta = entry.metadata()?;
+        if meta.is_dir() {
+            total += dir_size_strict(&entry.path())?;
+        } else {
+            total += meta.len();
+        }
+    }
+    Ok(total)
+}
+
+/// Espaco liberado ao remover um ou mais apps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiskSavingsEstimate {
+    pub total_app_bytes: u64,
+    pub total_residual_bytes: u64,
+    /// Soma das duas parcelas.
+    pub total_bytes: u64,
+}
+
+impl DiskSavingsEstimate {
+    fn new(total_app_bytes: u64, related: &[RelatedFile]) -> Self {
+        let total_residual_bytes = related.iter().map(|f| f.size).sum();
+        DiskSavingsEstimate {
+            total_app_bytes,
+            total_residual_bytes,
+            total_bytes: total_app_bytes + total_residual_bytes,
+        }
+    }
+}
+
+impl From<&RemovalPlan> for DiskSavingsEstimate {
+    fn from(plan: &RemovalPlan) -> Self {
+        DiskSavingsEstimate::new(plan.app_size, &plan.related)
+    }
+}
+
+impl std::iter::Sum for DiskSavingsEstimate {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(DiskSavingsEstimate::default(), |a, b| DiskSavingsEstimate {
+            total_app_bytes: a.total_app_bytes + b.total_app_bytes,
+            total_residual_bytes: a.total_residual_bytes + b.total_residual_bytes,
+            total_bytes: a.total_bytes + b.total_bytes,
+        })
+    }
+}
+
+/// Soma o `AppInfo::size` ja calculado com o tamanho dos arquivos residuais que
+/// `plan_removal` poria no plano com as opcoes padrao, buscados em paralelo. O
+/// tamanho dos apps nao e relido.
+pub fn estimate_disk_savings(apps: &[&AppInfo]) -> DiskSavingsEstimate {
+    estimate_disk_savings_in(&get_home(), apps)
+}
+
+fn estimate_disk_savings_in(home: &Path, apps: &[&AppInfo]) -> DiskSavingsEstimate {
+    apps.par_iter()
+        .map(|app| {
+            let stem = app.path.file_stem().unwrap_or_default().to_string_lossy();
+            let related = plan_related_files_in(
+                home,
+                &app.path,
+                &stem,
+                app.bundle_id.as_deref(),
+                &RemovalOptions::default(),
+                &Config::default(),
+            );
+            DiskSavingsEstimate::new(app.size, &related)
+        })
+        .sum()
+}
+
+/// Tamanho total dos arquivos residuais dos apps, buscados em paralelo.
+pub fn find_related_files_total_size(apps: &[&AppInfo]) -> u64 {
+    estimate_disk_savings(apps).total_residual_bytes
+}
+
+/// Estimativa rapida do tamanho via `du -sk`, limitada a 1 segundo.
+/// Retorna 0 se o `du` falhar ou demorar demais; use `dir_size` para o valor exato.
+pub fn estimate_removal_size(app_path: &Path) -> u64 {
+    let mut child = match Command::new("du")
+        .arg("-sk")
+        .arg(app_path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(c) => c,
+        Err(_) => return 0,
+    };
+
+    let deadline = Instant::now() + Duration::from_secs(1);
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(20)),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return 0;
+            }
+        }
+    }
+
+    let mut output = String::new();
+    if let Some(mut stdout) = child.stdout.take() {
+        let _ = stdout.read_to_string(&mut output);
+    }
+    output
+        .split_whitespace()
+        .next()
+        .and_then(|kb| kb.parse::<u64>().ok())
+        .map(|kb| kb * 1024)
+        .unwrap_or(0)
+}
+
+/// Separadores usados na formatacao de numeros.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    pub decimal_sep: char,
+    pub thousands_sep: Option<char>,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale {
+            decimal_sep: '.',
+            thousands_sep: None,
+        }
+    }
+}
+
+impl Locale {
+    /// Locale do sistema a partir de `LC_ALL`, `LC_NUMERIC` ou `LANG` (ex: "pt_BR.UTF-8").
+    pub fn system() -> Self {
+        ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .map(|value| Locale::from_name(&value))
+            .unwrap_or_default()
+    }
+
+    fn from_name(name: &str) -> Self {
+        let lang = name
+            .split(['_', '.', '@', '-'])
+            .next()
+            .unwrap_or("")
+            .to_lowercase();
+        match lang.as_str() {
+            "pt" | "de" | "es" | "it" | "nl" | "da" | "tr" | "id" => Locale {
+                decimal_sep: ',',
+                thousands_sep: Some('.'),
+            },
+            "fr" | "ru" | "sv" | "pl" | "cs" | "fi" | "nb" | "uk" => Locale {
+                decimal_sep: ',',
+                thousands_sep: Some('\u{a0}'),
+            },
+            "c" | "posix" | "" => Locale::default(),
+            _ => Locale {
+                decimal_sep: '.',
+                thousands_sep: Some(','),
+            },
+        }
+    }
+}
+
+pub fn format_size(bytes: u64) -> String {
+    format_size_locale(bytes, &Locale::default())
+}
+
+pub fn format_size_locale(bytes: u64, locale: &Locale) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = KB * 1024;
+    const GB: u64 = MB * 1024;
+
+    let (value, unit) = if bytes >= GB {
+        (bytes as f64 / GB as f64, "GB")
+    } else if bytes >= MB {
+        (bytes as f64 / MB as f64, "MB")
+    } else if bytes >= KB {
+        (bytes as f64 / KB as f64, "KB")
+    } else {
+        return format!("{} B", group_thousands(&bytes.to_string(), locale));
+    };
+
+    let formatted = format!("{:.1}", value);
+    let (int_part, frac_part) = formatted.split_once('.').unwrap_or((&formatted, "0"));
+    format!(
+        "{}{}{} {}",
+        group_thousands(int_part, locale),
+        locale.decimal_sep,
+        frac_part,
+        unit
+    )
+}
+
+fn group_thousands(digits: &str, locale: &Locale) -> String {
+    let sep = match locale.thousands_sep {
+        Some(sep) => sep,
+        None => return digits.to_string(),
+    };
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(sep);
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Entrada do historico de remocoes (ver `append_removal_record`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemovalRecord {
+    pub app_name: String,
+    pub bundle_id: Option<String>,
+    /// Segundos desde 1970 (UTC).
+    pub timestamp: u64,
+    pub strategy: RemovalStrategy,
+    /// Caminhos originais do que foi removido (o app primeiro).
+    pub removed: Vec<PathBuf>,
+    /// `(caminho original, caminho na Lixeira)` de cada item movido. Registros
+    /// antigos nao tem; `restore_from_trash` entao procura pelo nome.
+    #[serde(default)]
+    pub trashed: Vec<(PathBuf, PathBuf)>,
+}
+
+impl RemovalRecord {
+    /// Registro de uma remocao recem-executada.
+    pub fn new(
+        result: &RemovalResult,
+        bundle_id: Option<String>,
+        strategy: RemovalStrategy,
+    ) -> Self {
+        RemovalRecord {
+            app_name: result
+                .app_path
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            bundle_id,
+            timestamp: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            strategy,
+            removed: result.removed.clone(),
+            trashed: result.trashed.clone(),
+        }
+    }
+}
+
+/// Resultado de `restore_from_trash`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RestorationResult {
+    /// Caminhos originais que voltaram da Lixeira.
+    pub restored: Vec<PathBuf>,
+    /// Nada com esse nome na Lixeira (ja esvaziada, ou apagado de vez).
+    pub not_found: Vec<PathBuf>,
+    /// Ja existe algo no caminho original; o item ficou na Lixeira.
+    pub conflicts: Vec<PathBuf>,
+    /// Nao voltou por erro de E/S (ex: permissao), com a mensagem; os demais
+    /// itens continuam sendo restaurados.
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+/// Desfaz uma remocao feita com `RemovalStrategy::MoveToTrash`, devolvendo cada
+/// caminho do registro ao lugar original, a partir do destino guardado em
+/// `RemovalRecord::trashed`. Registros antigos, sem esse destino, procuram pelo
+/// nome: com varias copias (`Foo.app`, `Foo 2.app`...), usa a de maior numero,
+/// que e a movida por ultimo.
+pub fn restore_from_trash(record: &RemovalRecord) -> Result<RestorationResult, RemoverError> {
+    restore_from_trash_in(record, &get_home().join(".Trash"))
+}
+
+fn restore_from_trash_in(
+    record: &RemovalRecord,
+    trash: &Path,
+) -> Result<RestorationResult, RemoverError> {
+    let mut result = RestorationResult::default();
+    // As pastas de fora voltam antes do que estava dentro delas.
+    let mut removed: Vec<&PathBuf> = record.removed.iter().collect();
+    removed.sort_by_key(|path| nesting_depth(path, &record.removed));
+    for original in removed {
+        let recorded = record.trashed.iter().find(|(o, _)| o == original);
+        let trashed = match recorded {
+            Some((_, dest)) => fs::symlink_metadata(dest).is_ok().then(|| dest.clone()),
+            None => original
+                .file_name()
+                .and_then(|name| latest_in_trash(trash, Path::new(name))),
+        };
+        let Some(trashed) = trashed else {
+            result.not_found.push(original.clone());
+            continue;
+        };
+        if fs::symlink_metadata(original).is_ok() {
+            result.conflicts.push(original.clone());
+            continue;
+        }
+        let moved = match original.parent() {
+            Some(parent) => fs::create_dir_all(parent),
+            None => Ok(()),
+        }
+        .and_then(|()| fs::rename(&trashed, original));
+        match moved {
+            Ok(()) => result.restored.push(original.clone()),
+            Err(e) => result.failed.push((original.clone(), e.to_string())),
+        }
+    }
+    Ok(result)
+}
+
+/// `name` na Lixeira, ou a copia numerada mais recente (ver `trash_destination`).
+fn latest_in_trash(trash: &Path, name: &Path) -> Option<PathBuf> {
+    let exact = trash.join(name);
+    let mut n = 2;
+    let mut latest = fs::symlink_metadata(&exact).is_ok().then_some(exact);
+    loop {
+        let candidate = trash.join(numbered_copy_name(name, n));
+        if fs::symlink_metadata(&candidate).is_err() {
+            return latest;
+        }
+        latest = Some(candidate);
+        n += 1;
+    }
+}
+
+/// Quantas remocoes o historico guarda; as mais antigas sao descartadas.
+pub const DEFAULT_HISTORY_LIMIT: usize = 50;
+
+/// `~/.local/share/mac-app-remover/history.json`.
+pub fn history_path() -> PathBuf {
+    get_home().join(".local/share/mac-app-remover/history.json")
+}
+
+/// Acrescenta `record` ao historico, mantendo as `DEFAULT_HISTORY_LIMIT` mais recentes.
+pub fn append_removal_record(record: RemovalRecord) -> io::Result<()> {
+    append_removal_record_with_limit(record, DEFAULT_HISTORY_LIMIT)
+}
+
+/// Igual a `append_removal_record`, com outro limite de entradas.
+pub fn append_removal_record_with_limit(record: RemovalRecord, limit: usize) -> io::Result<()> {
+    append_removal_record_in(&history_path(), record, limit)
+}
+
+fn append_removal_record_in(file: &Path, record: RemovalRecord, limit: usize) -> io::Result<()> {
+    // No arquivo as entradas ficam em ordem cronologica.
+    let mut entries: VecDeque<RemovalRecord> = read_history(file).into();
+    entries.push_back(record);
+    while entries.len() > limit {
+        entries.pop_front();
+    }
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_string_pretty(&entries).map_err(io::Error::other)?;
+    fs::write(file, json)
+}
+
+/// Historico de remocoes, da mais recente para a mais antiga.
+pub fn get_removal_history() -> Vec<RemovalRecord> {
+    get_removal_history_in(&history_path())
+}
+
+fn get_removal_history_in(file: &Path) -> Vec<RemovalRecord> {
+    let mut entries = read_history(file);
+    entries.reverse();
+    entries
+}
+
+/// Sem arquivo (ou com um corrompido) o historico comeca vazio.
+fn read_history(file: &Path) -> Vec<RemovalRecord> {
+    fs::read_to_string(file)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// `AAAA-MM-DD HH:MM` (UTC) de um `RemovalRecord::timestamp`.
+pub fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let (year, month, day) = civil_from_days(days);
+    let rem = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60
+    )
+}
+
+/// Inverso de `days_from_civil`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+pub fn get_home() -> PathBuf {
+    PathBuf::from(env::var("HOME").unwrap_or_else(|_| "/Users/unknown".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Diretorio temporario removido ao sair do escopo.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            // `RUST_LOG=debug cargo test --features log` mostra o que a busca visitou.
+            #[cfg(feature = "log")]
+            let _ = env_logger::builder().is_test(true).try_init();
+            let path =
+                env::temp_dir().join(format!("mac-app-remover-{}-{}", std::process::id(), name));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn touch(path: &Path) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, b"x").unwrap();
+    }
+
+    fn found_paths(home: &Path, app_name: &str, bundle_id: Option<&str>) -> Vec<PathBuf> {
+        find_related_files_in(home, app_name, bundle_id, &Config::default())
+            .into_iter()
+            .map(|f| f.path)
+            .collect()
+    }
+
+    #[test]
+    fn caches_second_level_matches_bundle_id_prefix() {
+        let tmp = TempDir::new("caches-second-level");
+        let caches = tmp.0.join("Library/Caches");
+        touch(&caches.join("Vendor/com.example.app.helper/cache.db"));
+        touch(&caches.join("Vendor/com.other.app/cache.db"));
+        touch(&caches.join("com.example.app/cache.db"));
+
+        let found = found_paths(&tmp.0, "Example", Some("com.example.app"));
+        assert!(found.contains(&caches.join("Vendor/com.example.app.helper")));
+        assert!(found.contains(&caches.join("com.example.app")));
+        assert!(!found.contains(&caches.join("Vendor/com.other.app")));
+        assert!(!found.contains(&caches.join("Vendor")));
+    }
+
+    #[test]
+    fn mail_plugins_match_mailbundle_by_app_name() {
+        let tmp = TempDir::new("mail-plugins");
+        let bundles = tmp.0.join("Library/Mail/Bundles");
+        fs::create_dir_all(bundles.join("GPGMail.mailbundle")).unwrap();
+        fs::create_dir_all(bundles.join("GPGMail Notes")).unwrap();
+        fs::create_dir_all(bundles.join("Other.mailbundle")).unwrap();
+
+        let plugins = find_mail_plugins_in(&tmp.0, "gpgmail");
+        assert_eq!(plugins, vec![bundles.join("GPGMail.mailbundle")]);
+    }
+
+    #[test]
+    fn quarantine_value_is_parsed() {
+        let info =
+            parse_quarantine_value("0083;5f1b2c3d;Safari;6D2A3C9E-0000-0000-0000-000000000000");
+        assert_eq!(
+            info.timestamp,
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(0x5f1b2c3d))
+        );
+        assert_eq!(info.agent_bundle_id.as_deref(), Some("Safari"));
+
+        let info = parse_quarantine_value("0081;;;");
+        assert_eq!(info.timestamp, None);
+        assert_eq!(info.agent_bundle_id, None);
+    }
+
+    #[test]
+    fn ps_output_is_filtered_by_bundle_id() {
+        let output = "  PID COMM\n\
+                      1 /sbin/launchd\n\
+                      412 /Applications/Zoom.app/Contents/MacOS/zoom.us\n\
+                      413 /Library/Helpers/us.zoom.xos.Helper\n\
+                      500 /Applications/Zoom Helper/us.zoom.xos.Helper (Renderer)\n\
+                      501 /Applications/Other.app/Contents/MacOS/us.zoom.xosomething\n\
+                      502 /opt/tools/not-us.zoom.xos.Helper\n";
+        let processes = processes_matching(parse_ps_output(output), "us.zoom.xos");
+        assert_eq!(
+            processes,
+            vec![
+                (413, PathBuf::from("/Library/Helpers/us.zoom.xos.Helper")),
+                (
+                    500,
+                    PathBuf::from("/Applications/Zoom Helper/us.zoom.xos.Helper (Renderer)")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn app_bundles_are_collected_up_to_max_depth() {
+        let tmp = TempDir::new("collect-app-bundles");
+        fs::create_dir_all(tmp.0.join("Top.app/Contents/Helpers/Inner.app")).unwrap();
+        fs::create_dir_all(tmp.0.join("Utilities/Terminal.app")).unwrap();
+        fs::create_dir_all(tmp.0.join("Adobe/Deep/Hidden.app")).unwrap();
+
+        let mut apps = Vec::new();
+        collect_app_bundles(&tmp.0, 1, &mut apps);
+        assert_eq!(apps, vec![tmp.0.join("Top.app")]);
+
+        let mut apps = Vec::new();
+        collect_app_bundles(&tmp.0, 2, &mut apps);
+        apps.sort();
+        assert_eq!(
+            apps,
+            vec![tmp.0.join("Top.app"), tmp.0.join("Utilities/Terminal.app")]
+        );
+    }
+
+    #[test]
+    fn format_size_locale_uses_separators() {
+        let pt = Locale::from_name("pt_BR.UTF-8");
+        assert_eq!(format_size_locale(1536, &pt), "1,5 KB");
+        assert_eq!(
+            format_size_locale(2048 * 1024 * 1024 * 1024, &pt),
+            "2.048,0 GB"
+        );
+        assert_eq!(format_size_locale(512, &pt), "512 B");
+
+        let en = Locale::from_name("en_US.UTF-8");
+        assert_eq!(format_size_locale(1536, &en), "1.5 KB");
+        assert_eq!(
+            format_size_locale(2048 * 1024 * 1024 * 1024, &en),
+            "2,048.0 GB"
+        );
+
+        assert_eq!(Locale::from_name("C"), Locale::default());
+        assert_eq!(format_size(1536), "1.5 KB");
+    }
+
+    #[test]
+    fn finds_contacts_plugins() {
+        let tmp = TempDir::new("contacts-plugins");
+        let plugins = tmp
+            .0
+            .join("Library/Application Support/AddressBook/Plug-Ins");
+        touch(&plugins.join("CardHopSync.bundle/Contents/Info.plist"));
+        touch(&plugins.join("Other.bundle/Contents/Info.plist"));
+        touch(&plugins.join("CardHop.txt"));
+
+        let found = find_related_files_in(&tmp.0, "CardHop", None, &Config::default());
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, plugins.join("CardHopSync.bundle"));
+        assert_eq!(found[0].category, RelatedFileCategory::ContactsPlugin);
+    }
+
+    #[test]
+    fn parses_dates() {
+        let day = |n: u64| SystemTime::UNIX_EPOCH + Duration::from_secs(n * 86_400);
+        assert_eq!(parse_date("1970-01-01"), Some(day(0)));
+        assert_eq!(parse_date("2024-01-01"), Some(day(19_723)));
+        assert_eq!(parse_date("2024-03-01"), Some(day(19_783)));
+        assert_eq!(parse_date("2024-13-01"), None);
+        assert_eq!(parse_date("2024-02-29"), Some(day(19_782)));
+        assert_eq!(parse_date("2023-02-29"), None);
+        assert_eq!(parse_date("2024-02-31"), None);
+        assert_eq!(parse_date("2024-04-31"), None);
+        assert_eq!(parse_date("1900-02-29"), None);
+        assert_eq!(parse_date("ontem"), None);
+
+        assert_eq!(
+            parse_mdls_date("2024-01-01 10:20:30 +0000\n"),
+            Some(day(19_723) + Duration::from_secs(10 * 3600 + 20 * 60 + 30))
+        );
+        assert_eq!(
+            parse_mdls_date("2024-01-01 00:00:00 -0300"),
+            Some(day(19_723) + Duration::from_secs(3 * 3600))
+        );
+        assert_eq!(parse_mdls_date("(null)"), None);
+    }
+
+    #[test]
+    fn remove_path_with_retry_does_not_retry_missing_files() {
+        let tmp = TempDir::new("retry-missing");
+        let start = Instant::now();
+        let err = remove_path_with_retry(&tmp.0.join("missing"), 5, Duration::from_secs(1));
+        assert!(matches!(err, Err(RemoverError::Io(e)) if e.kind() == io::ErrorKind::NotFound));
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        let file = tmp.0.join("file");
+        touch(&file);
+        remove_path_with_retry(&file, 1, Duration::ZERO).unwrap();
+        assert!(!file.exists());
+    }
+
+    #[test]
+    fn format_size_boundaries() {
+        const KB: u64 = 1024;
+        const MB: u64 = KB * 1024;
+        const GB: u64 = MB * 1024;
+
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1), "1 B");
+        assert_eq!(format_size(KB - 1), "1023 B");
+        assert_eq!(format_size(KB), "1.0 KB");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(MB - 1), "1024.0 KB");
+        assert_eq!(format_size(MB), "1.0 MB");
+        assert_eq!(format_size(GB - 1), "1024.0 MB");
+        assert_eq!(format_size(GB), "1.0 GB");
+        assert_eq!(format_size(GB + GB / 2), "1.5 GB");
+        assert_eq!(format_size(u64::MAX), "17179869184.0 GB");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dir_size_skips_unreadable_subdirectories() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = TempDir::new("dir-size-unreadable");
+        fs::write(tmp.0.join("a"), [0u8; 10]).unwrap();
+        let locked = tmp.0.join("locked");
+        fs::create_dir(&locked).unwrap();
+        fs::write(locked.join("b"), [0u8; 20]).unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+        // root ignora permissoes; nesse caso o diretorio continua legivel.
+        let readable = fs::read_dir(&locked).is_ok();
+        let size = dir_size(&tmp.0).unwrap();
+        let strict = dir_size_strict(&tmp.0);
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        if readable {
+            assert_eq!(size, 30);
+            assert_eq!(strict.unwrap(), 30);
+        } else {
+            assert_eq!(size, 10);
+            assert!(strict.is_err());
+        }
+    }
+
+    #[test]
+    fn content_creator_query_escapes_quotes() {
+        assert_eq!(
+            content_creator_query("Slack"),
+            "kMDItemContentCreator == 'Slack'"
+        );
+        assert_eq!(
+            content_creator_query("Bob's App"),
+            "kMDItemContentCreator == 'Bob\\'s App'"
+        );
+    }
+
+    #[test]
+    fn mdfind_bundle_ids_are_parsed() {
+        let output = "/Applications/Safari.app   kMDItemCFBundleIdentifier = com.apple.Safari\n\
+                      /Applications/My App.app   kMDItemCFBundleIdentifier = com.example.my-app\n\
+                      /Applications/Broken.app   kMDItemCFBundleIdentifier = (null)\n";
+        let ids = parse_mdfind_bundle_ids(output);
+        assert_eq!(ids.len(), 2);
+        assert_eq!(
+            ids.get(Path::new("/Applications/My App.app"))
+                .map(String::as_str),
+            Some("com.example.my-app")
+        );
+        assert!(!ids.contains_key(Path::new("/Applications/Broken.app")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_app_bundles_are_deduplicated() {
+        let tmp = TempDir::new("symlink-apps");
+        let real = tmp.0.join("Caskroom/Foo.app");
+        let link = tmp.0.join("Applications/Foo.app");
+        fs::create_dir_all(&real).unwrap();
+        fs::create_dir_all(link.parent().unwrap()).unwrap();
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+        fs::create_dir_all(tmp.0.join("Applications/Bar.app")).unwrap();
+
+        let mut apps = vec![real, link.clone(), tmp.0.join("Applications/Bar.app")];
+        dedup_app_bundles(&mut apps);
+        apps.sort();
+        assert_eq!(apps, vec![tmp.0.join("Applications/Bar.app"), link]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_entries_are_deduplicated() {
+        let tmp = TempDir::new("symlink-dedup");
+        let containers = tmp.0.join("Library/Containers");
+        fs::create_dir_all(containers.join("com.example.app/Data")).unwrap();
+        std::os::unix::fs::symlink(
+            containers.join("com.example.app"),
+            containers.join("Example Link"),
+        )
+        .unwrap();
+
+        let found = found_paths(&tmp.0, "Example", Some("com.example.app"));
+        assert_eq!(found, vec![containers.join("com.example.app")]);
+    }
+
+    #[test]
+    fn match_modes() {
+        let id = Some("com.example.app");
+        assert!(MatchMode::SubstringAny.matches("Example Data", "example", id));
+        assert!(MatchMode::SubstringAny.matches("com.example.app.plist", "Other", id));
+        assert!(!MatchMode::SubstringAny.matches("Unrelated", "Example", id));
+
+        assert!(MatchMode::ExactBundleId.matches("com.Example.App", "Example", id));
+        assert!(!MatchMode::ExactBundleId.matches("com.example.app.helper", "Example", id));
+        assert!(!MatchMode::ExactBundleId.matches("com.example.app", "Example", None));
+
+        assert!(MatchMode::PrefixBundleId.matches("com.example.app.helper", "Example", id));
+        assert!(!MatchMode::PrefixBundleId.matches("Example", "Example", id));
+    }
+
+    #[test]
+    fn helpd_cache_matches_bundle_id_in_subdirectories() {
+        let tmp = TempDir::new("helpd");
+        let helpd = tmp.0.join("Library/Caches/com.apple.helpd");
+        touch(&helpd.join("Generated/com.example.app.help*1.0/index"));
+        touch(&helpd.join("Generated/com.other.help/index"));
+        touch(&helpd.join("SDMHelpData/Other/Cache/com.example.app.helpindex"));
+
+        let found = found_paths(&tmp.0, "Example", Some("com.example.app"));
+        assert_eq!(
+            found,
+            vec![
+                helpd.join("Generated/com.example.app.help*1.0"),
+                helpd.join("SDMHelpData/Other/Cache/com.example.app.helpindex"),
+            ]
+        );
+    }
+
+    #[test]
+    fn system_library_support_requires_elevation() {
+        let tmp = TempDir::new("system-support");
+        touch(&tmp.0.join("Library/Application Support/Parallels/vm.hdd"));
+        touch(&tmp.0.join("Library/Application Support/Other/data"));
+
+        let found = find_system_library_support_in(&tmp.0.join("Library"), "Parallels", None);
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0].path,
+            tmp.0.join("Library/Application Support/Parallels")
+        );
+        assert!(found[0].requires_elevation);
+    }
+
+    #[test]
+    fn byhost_preferences_match_bundle_id_prefix() {
+        let tmp = TempDir::new("byhost");
+        let byhost = tmp.0.join("Library/Preferences/ByHost");
+        touch(&byhost.join("com.example.app.0A1B2C3D-4E5F-6789-ABCD-EF0123456789.plist"));
+        touch(&byhost.join("com.apple.loginwindow.0A1B2C3D-4E5F-6789-ABCD-EF0123456789.plist"));
+
+        let found = found_paths(&tmp.0, "Nothing", Some("com.example.app"));
+        assert_eq!(
+            found,
+            vec![byhost.join("com.example.app.0A1B2C3D-4E5F-6789-ABCD-EF0123456789.plist")]
+        );
+    }
+
+    #[test]
+    fn metadata_cache_matches_bundle_id_two_levels_deep() {
+        let tmp = TempDir::new("metadata");
+        let metadata = tmp.0.join("Library/Caches/Metadata");
+        touch(&metadata.join("Projects/com.apple.FinalCut.library/Events.db"));
+        touch(&metadata.join("Projects/com.other.app/Events.db"));
+
+        let found = found_paths(&tmp.0, "Final Cut Pro", Some("com.apple.FinalCut"));
+        assert_eq!(
+            found,
+            vec![metadata.join("Projects/com.apple.FinalCut.library")]
+        );
+    }
+
+    #[test]
+    fn chromium_cache_subfolders_are_sized_inside_the_cache() {
+        let tmp = TempDir::new("chromium-cache");
+        let cache = tmp.0.join("Library/Caches/com.tinyspeck.slackmacgap");
+        touch(&cache.join("GPUCache/data_0"));
+        touch(&cache.join("blob_storage/abc/1"));
+        touch(&cache.join("blob_storage/abc/2"));
+        touch(&cache.join("Cache.db"));
+        touch(&tmp.0.join("Library/Caches/com.other.app/GPUCache/data_0"));
+
+        let id = Some("com.tinyspeck.slackmacgap");
+        let related = find_related_files_in(&tmp.0, "Nothing", id, &Config::default());
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].path, cache);
+        assert_eq!(related[0].size, 4);
+        assert_eq!(
+            related[0].contents,
+            [(cache.join("blob_storage"), 2), (cache.join("GPUCache"), 1)]
+        );
+    }
+
+    #[test]
+    fn electron_subfolders_are_sized_inside_the_support_folder() {
+        let tmp = TempDir::new("electron-support");
+        let support = tmp.0.join("Library/Application Support/Slack");
+        touch(&support.join("Code Cache/js/index"));
+        touch(&support.join("Service Worker/CacheStorage/x"));
+        touch(&support.join("storage/root-state.json"));
+
+        assert_eq!(
+            find_electron_app_data_in(&tmp.0, "Slack"),
+            [support.join("Code Cache"), support.join("Service Worker")]
+        );
+        let related = find_related_files_in(&tmp.0, "Slack", None, &Config::default());
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].path, support);
+        assert_eq!(
+            related[0].contents,
+            [
+                (support.join("Code Cache"), 1),
+                (support.join("Service Worker"), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn sparkle_updates_get_their_own_category() {
+        let tmp = TempDir::new("sparkle");
+        let lib = tmp.0.join("Library");
+        let support = lib.join("Application Support/Bar");
+        touch(&support.join("Sparkle/Bar-2.0.dmg"));
+        touch(&support.join("settings.json"));
+        let cache = lib.join("Caches/com.vendor.app");
+        touch(&cache.join("org.sparkle-project.Sparkle/Installation/Bar.app/x"));
+
+        assert_eq!(
+            find_sparkle_cache_in(&tmp.0, "Bar", Some("com.vendor.app")),
+            [
+                support.join("Sparkle"),
+                cache.join("org.sparkle-project.Sparkle")
+            ]
+        );
+        let related =
+            find_related_files_in(&tmp.0, "Bar", Some("com.vendor.app"), &Config::default());
+        let found: Vec<_> = related
+            .iter()
+            .map(|f| (f.path.clone(), f.category, f.size))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (support.clone(), RelatedFileCategory::AppSupport, 1),
+                (support.join("Sparkle"), RelatedFileCategory::UpdateCache, 1),
+                (cache.clone(), RelatedFileCategory::Cache, 0),
+                (
+                    cache.join("org.sparkle-project.Sparkle"),
+                    RelatedFileCategory::UpdateCache,
+                    1
+                ),
+            ]
+        );
+        // Mantendo os caches, a pasta que contem o Sparkle tambem fica.
+        let opts = RemovalOptions {
+            keep_caches: true,
+            ..Default::default()
+        };
+        assert!(filter_related_files(related, &opts).is_empty());
+    }
+
+    #[test]
+    fn realm_databases_are_shown_with_companions_inside_the_folder() {
+        let tmp = TempDir::new("realm");
+        let support = tmp.0.join("Library/Application Support/Bar");
+        touch(&support.join("default.realm"));
+        touch(&support.join("default.realm.lock"));
+        touch(&support.join("default.realm.management/access_control.control.mx"));
+        touch(&support.join("Images/cover.png"));
+
+        assert_eq!(
+            find_realm_files(&support),
+            [
+                support.join("default.realm"),
+                support.join("default.realm.lock"),
+                support.join("default.realm.management"),
+            ]
+        );
+        let id = Some("com.vendor.app");
+        let related = find_related_files_in(&tmp.0, "Bar", id, &Config::default());
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].path, support);
+        assert_eq!(
+            related[0].contents,
+            [
+                (support.join("default.realm"), 1),
+                (support.join("default.realm.lock"), 1),
+                (support.join("default.realm.management"), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn support_databases_are_shown_inside_the_folder() {
+        let tmp = TempDir::new("grdb");
+        let support = tmp.0.join("Library/Application Support/Bar");
+        touch(&support.join("Data/db.sqlite"));
+        touch(&support.join("Data/db.sqlite-wal"));
+        touch(&support.join("Data/Archive/2023.grdb"));
+        touch(&support.join("Data/Archive/readme.txt"));
+        touch(&support.join("Images/cover.png"));
+
+        assert_eq!(
+            find_database_files(&support),
+            [
+                support.join("Data/Archive/2023.grdb"),
+                support.join("Data/db.sqlite")
+            ]
+        );
+        let id = Some("com.vendor.app");
+        let related = find_related_files_in(&tmp.0, "Bar", id, &Config::default());
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].path, support);
+        assert_eq!(
+            related[0].contents,
+            [
+                (support.join("Data/Archive/2023.grdb"), 1),
+                (support.join("Data/db.sqlite"), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn crashlytics_reports_are_crash_logs() {
+        let tmp = TempDir::new("crashlytics");
+        let cache = tmp.0.join("Library/Caches/com.vendor.app");
+        touch(&cache.join("com.crashlytics.data/com.vendor.app/v5/reports/a.clsrecord"));
+        touch(&cache.join("Cache.db"));
+
+        let related =
+            find_related_files_in(&tmp.0, "Bar", Some("com.vendor.app"), &Config::default());
+        let found: Vec<_> = related
+            .iter()
+            .map(|f| (f.path.clone(), f.category, f.size))
+            .collect();
+        let reports = cache.join("com.crashlytics.data");
+        assert_eq!(
+            found,
+            [
+                (cache, RelatedFileCategory::Cache, 1),
+                (reports.clone(), RelatedFileCategory::CrashLog, 1),
+            ]
+        );
+        // Mantendo os caches, so os relatorios saem.
+        let opts = RemovalOptions {
+            keep_caches: true,
+            ..Default::default()
+        };
+        let kept: Vec<_> = filter_related_files(related, &opts)
+            .into_iter()
+            .map(|f| f.path)
+            .collect();
+        assert_eq!(kept, [reports]);
+    }
+
+    #[test]
+    fn cli_tools_skip_links_owned_by_other_apps_and_homebrew() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let tmp = TempDir::new("cli-tools");
+        let bin = tmp.0.join("bin");
+        fs::create_dir_all(&bin).unwrap();
+        let code = "/Applications/Visual Studio Code.app/Contents/Resources/app/bin/code";
+        symlink(code, bin.join("code")).unwrap();
+        symlink(
+            "/Applications/Other.app/Contents/MacOS/other",
+            bin.join("vscode-other"),
+        )
+        .unwrap();
+        symlink(
+            "../Cellar/visualstudiocode-tools/1.0/bin/vscode",
+            bin.join("vscode"),
+        )
+        .unwrap();
+        for tool in ["visualstudiocode-helper", "notes"] {
+            touch(&bin.join(tool));
+            fs::set_permissions(bin.join(tool), fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        touch(&bin.join("visualstudiocode.txt"));
+
+        let dirs = [bin.clone()];
+        let (linked, by_name) =
+            find_installed_cli_tools_in(&dirs, "Visual Studio Code", "com.microsoft.Code");
+        assert_eq!(linked, [bin.join("code")]);
+        assert_eq!(by_name, [bin.join("visualstudiocode-helper")]);
+
+        // So viram aviso, fora do plano.
+        let app = tmp.0.join("Visual Studio Code.app");
+        fs::create_dir_all(&app).unwrap();
+        let warnings =
+            plan_warnings_in(&app, Some("com.microsoft.Code"), &tmp.0, &[], &tmp.0, &dirs);
+        assert_eq!(
+            warnings,
+            [Warning::CliToolNameMatch {
+                paths: vec![bin.join("visualstudiocode-helper")]
+            }]
+        );
+    }
+
+    #[test]
+    fn sqlite_sidecars_follow_their_database() {
+        let tmp = TempDir::new("sqlite");
+        let caches = tmp.0.join("Library/Caches");
+        touch(&caches.join("com.foo.app.db"));
+        touch(&caches.join("com.foo.app.db-wal"));
+        touch(&caches.join("com.foo.app.db-shm"));
+        touch(&caches.join("Foo.sqlite"));
+        touch(&caches.join("Foo.sqlite-wal"));
+        let found = [
+            (caches.join("com.foo.app.db"), RelatedFileCategory::Cache),
+            (caches.join("Foo.sqlite"), RelatedFileCategory::AppSupport),
+        ];
+        assert_eq!(
+            sqlite_sidecars(&found),
+            [
+                (
+                    caches.join("com.foo.app.db-wal"),
+                    RelatedFileCategory::Cache
+                ),
+                (
+                    caches.join("com.foo.app.db-shm"),
+                    RelatedFileCategory::Cache
+                ),
+                (
+                    caches.join("Foo.sqlite-wal"),
+                    RelatedFileCategory::AppSupport
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn saved_state_is_found_by_bundle_id() {
+        let tmp = TempDir::new("saved-state");
+        let state = tmp
+            .0
+            .join("Library/Saved Application State/com.vendor.app.savedState");
+        touch(&state.join("windows.plist"));
+
+        assert_eq!(
+            find_saved_state_in(&tmp.0, "com.vendor.app"),
+            Some(state.clone())
+        );
+        assert_eq!(find_saved_state_in(&tmp.0, "com.other.app"), None);
+        assert_eq!(
+            found_paths(&tmp.0, "Bar", Some("com.vendor.app")),
+            vec![state]
+        );
+    }
+
+    #[test]
+    fn container_tmp_is_covered_by_its_container() {
+        let tmp = TempDir::new("container-tmp");
+        let container = tmp.0.join("Library/Containers/com.foo.app");
+        touch(&container.join("Data/tmp/upload.part"));
+        assert_eq!(
+            find_container_tmp_in(&tmp.0, "com.foo.app"),
+            Some(container.join("Data/tmp"))
+        );
+        assert_eq!(find_container_tmp_in(&tmp.0, "com.other.app"), None);
+
+        let found = found_paths(&tmp.0, "Nothing", Some("com.foo.app"));
+        assert_eq!(found, vec![container]);
+    }
+
+    #[test]
+    fn xcode_caches_are_developer_files() {
+        let tmp = TempDir::new("xcode");
+        touch(
+            &tmp.0
+                .join("Library/Developer/Xcode/DerivedData/App-abc/info.plist"),
+        );
+        touch(&tmp.0.join("Library/Caches/com.apple.dt.Xcode/Cache.db"));
+        touch(
+            &tmp.0
+                .join("Library/Developer/CoreSimulator/Devices/device.plist"),
+        );
+
+        let related =
+            find_related_files_in(&tmp.0, "Xcode", Some(XCODE_BUNDLE_ID), &Config::default());
+        let developer: Vec<_> = related
+            .iter()
+            .filter(|f| f.category == RelatedFileCategory::Developer)
+            .map(|f| f.path.clone())
+            .collect();
+        assert_eq!(
+            developer,
+            [
+                tmp.0.join("Library/Caches/com.apple.dt.Xcode"),
+                tmp.0.join("Library/Developer/Xcode/DerivedData"),
+            ]
+        );
+    }
+
+    #[test]
+    fn instruments_cache_is_only_found_for_developer_tools() {
+        let tmp = TempDir::new("instruments");
+        let cache = tmp.0.join("Library/Caches/com.apple.dt.instruments");
+        touch(&cache.join("dsc/symbols"));
+
+        let find = |name, id| find_related_files_in(&tmp.0, name, Some(id), &Config::default());
+        let related = find("Instruments", "com.apple.dt.Instruments");
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].path, cache);
+        assert_eq!(related[0].category, RelatedFileCategory::Developer);
+        assert!(find("Bar", "com.vendor.app").is_empty());
+    }
+
+    #[test]
+    fn extension_containers_get_their_own_category() {
+        let tmp = TempDir::new("extensions");
+        let containers = tmp.0.join("Library/Containers");
+        touch(&containers.join("com.foo.app/Data/prefs"));
+        touch(&containers.join("com.foo.app.ShareExtension/Data/prefs"));
+        touch(&containers.join("com.foo.application/Data/prefs"));
+
+        let extensions = find_extension_containers_in(&tmp.0, "com.foo.app");
+        assert_eq!(
+            extensions,
+            vec![containers.join("com.foo.app.ShareExtension")]
+        );
+
+        let config = Config::default();
+        let related = find_related_files_in(&tmp.0, "Nothing", Some("com.foo.app"), &config);
+        let category = |name: &str| {
+            related
+                .iter()
+                .find(|f| f.path == containers.join(name))
+                .map(|f| f.category)
+        };
+        assert_eq!(
+            category("com.foo.app"),
+            Some(RelatedFileCategory::Container)
+        );
+        assert_eq!(
+            category("com.foo.app.ShareExtension"),
+            Some(RelatedFileCategory::ExtensionContainer)
+        );
+    }
+
+    #[test]
+    fn webkit_falls_back_to_app_name_without_bundle_id_match() {
+        let tmp = TempDir::new("webkit");
+        let webkit = tmp.0.join("Library/WebKit");
+        touch(&webkit.join("com.vendor.app/WebsiteData/data"));
+        touch(&webkit.join("Bar/WebsiteData/data"));
+        let webkit_files = |id| {
+            let related = find_related_files_in(&tmp.0, "Bar", Some(id), &Config::default());
+            related
+                .into_iter()
+                .filter(|f| f.category.is_web_data())
+                .map(|f| (f.path, f.category))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            webkit_files("com.vendor.app"),
+            [(webkit.join("com.vendor.app"), RelatedFileCategory::WebData)]
+        );
+        assert_eq!(
+            webkit_files("com.vendor.other"),
+            [(webkit.join("Bar"), RelatedFileCategory::WebDataByName)]
+        );
+    }
+
+    #[test]
+    fn http_storages_match_only_exact_bundle_id() {
+        let tmp = TempDir::new("httpstorages");
+        let storages = tmp.0.join("Library/HTTPStorages");
+        touch(&storages.join("com.apple.Safari/httpstorages.sqlite"));
+        touch(&storages.join("com.example.safaritools/httpstorages.sqlite"));
+
+        let found = found_paths(&tmp.0, "Safari", Some("com.example.safaritools"));
+        assert_eq!(found, vec![storages.join("com.example.safaritools")]);
+    }
+
+    #[test]
+    fn sync_services_client_data_is_found_with_a_note() {
+        let tmp = TempDir::new("sync-services");
+        let clients = tmp
+            .0
+            .join("Library/Application Support/SyncServices/Local/clientdata");
+        touch(&clients.join("com.vendor.app/data.syncdb"));
+        touch(&clients.join("com.apple.Mail/data.syncdb"));
+
+        let found =
+            find_related_files_in(&tmp.0, "Bar", Some("com.vendor.app"), &Config::default());
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, clients.join("com.vendor.app"));
+        assert_eq!(found[0].category, RelatedFileCategory::SyncServices);
+        assert_eq!(
+            removal_notes(&found),
+            [RelatedFileCategory::SyncServices.removal_note().unwrap()]
+        );
+    }
+
+    #[test]
+    fn nsurlsessiond_matches_exact_bundle_id() {
+        let tmp = TempDir::new("nsurlsessiond");
+        let sessiond = tmp.0.join("Library/com.apple.nsurlsessiond");
+        touch(&sessiond.join("Downloads/com.example.app/file.tmp"));
+        touch(&sessiond.join("Downloads/com.example.app.helper/file.tmp"));
+
+        let found = find_related_files_in(
+            &tmp.0,
+            "Nothing",
+            Some("com.example.app"),
+            &Config::default(),
+        );
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, sessiond.join("Downloads/com.example.app"));
+        assert_eq!(found[0].category, RelatedFileCategory::NetworkData);
+        assert_eq!(removal_notes(&found).len(), 1);
+    }
+
+    #[test]
+    fn biome_is_scanned_three_levels_deep() {
+        let tmp = TempDir::new("biome-depth");
+        let biome = tmp.0.join("Library/Biome");
+        touch(&biome.join("streams/public/com.example.app.usage/segment"));
+        touch(&biome.join("streams/public/deeper/com.example.app.other/segment"));
+
+        let found = found_paths(&tmp.0, "Example", Some("com.example.app"));
+        assert_eq!(
+            found,
+            vec![biome.join("streams/public/com.example.app.usage")]
+        );
+    }
+
+    #[test]
+    fn application_support_is_scanned_one_level_deep() {
+        let tmp = TempDir::new("app-support-one-level");
+        let support = tmp.0.join("Library/Application Support");
+        touch(&support.join("Vendor/com.example.app.helper/data"));
+
+        let found = found_paths(&tmp.0, "Example", Some("com.example.app"));
+        assert!(found.is_empty());
+    }
+
+    #[test]
+    fn logs_match_exact_folders_and_nested_files() {
+        let tmp = TempDir::new("logs-two-levels");
+        let logs = tmp.0.join("Library/Logs");
+        touch(&logs.join("MyApp/2024/01/session.log"));
+        touch(&logs.join("MyApp.log"));
+        touch(&logs.join("com.example.myapp.helper/helper.log"));
+        touch(&logs.join("DiagnosticReports/MyApp_2024-01-02-100000_mac.ips"));
+        touch(&logs.join("DiagnosticReports/Other_2024-01-02-100000_mac.ips"));
+        touch(&logs.join("com.apple.EmbeddedOSLog/com.example.myapp.tracev3"));
+        // Contem o nome, mas nao e a pasta do app.
+        touch(&logs.join("MyAppSync/sync.log"));
+
+        assert_eq!(
+            find_app_logs_in(&tmp.0, "MyApp", Some("com.example.myapp")),
+            [
+                logs.join("DiagnosticReports/MyApp_2024-01-02-100000_mac.ips"),
+                logs.join("MyApp"),
+                logs.join("MyApp.log"),
+                logs.join("com.apple.EmbeddedOSLog/com.example.myapp.tracev3"),
+                logs.join("com.example.myapp.helper"),
+            ]
+        );
+    }
+
+    #[test]
+    fn find_related_files_matches_fixture_tree_exactly() {
+        let tmp = TempDir::new("fixture-tree");
+        let lib = tmp.0.join("Library");
+        let expected = vec![
+            lib.join("Application Support/MyApp"),
+            lib.join("Caches/com.example.myapp"),
+            lib.join("Logs/MyApp"),
+            lib.join("Preferences/com.example.myapp.plist"),
+        ];
+        touch(&expected[0].join("settings.json"));
+        touch(&expected[1].join("Cache.db"));
+        touch(&expected[2].join("myapp.log"));
+        touch(&expected[3]);
+        // Nao pertencem ao app
+        touch(&lib.join("Application Support/OtherApp/data"));
+        touch(&lib.join("Caches/com.example.other/Cache.db"));
+        touch(&lib.join("Preferences/com.example.other.plist"));
+        touch(&lib.join("Logs/DiagnosticReports/crash.ips"));
+
+        let found = found_paths(&tmp.0, "MyApp", Some("com.example.myapp"));
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn finds_xpc_support_nested_in_other_containers() {
+        let tmp = TempDir::new("nested-xpc");
+        let containers = tmp.0.join("Library/Containers");
+        let own = containers.join("com.example.app");
+        let foreign = containers
+            .join("com.apple.Safari/Data/Library/Application Support/com.example.app.extension");
+        touch(&own.join("Data/Library/Application Support/com.example.app.xpc/state"));
+        touch(&foreign.join("state"));
+        touch(&containers.join("com.apple.Safari/Data/Library/Application Support/Other/x"));
+
+        let found = found_paths(&tmp.0, "Example", Some("com.example.app"));
+        // O container proprio ja inclui o servico XPC; nao aparece duas vezes.
+        assert_eq!(found, vec![foreign, own]);
+    }
+
+    #[test]
+    fn parses_team_id_from_designated_requirement() {
+        let req = "Executable=/Applications/Example.app/Contents/MacOS/Example\n\
+            designated => anchor apple generic and identifier \"com.example.app\" and \
+            (certificate leaf[field.1.2.840.113635.100.6.1.9] /* exists */ or \
+            certificate 1[field.1.2.840.113635.100.6.2.6] /* exists */ and \
+            certificate leaf[subject.OU] = ABCD1234EF)\n";
+        assert_eq!(parse_team_id(req).as_deref(), Some("ABCD1234EF"));
+        let quoted = "designated => anchor apple generic and \
+            certificate leaf[subject.OU] = \"XYZ9876543\"";
+        assert_eq!(parse_team_id(quoted).as_deref(), Some("XYZ9876543"));
+        assert_eq!(parse_team_id("designated => anchor apple"), None);
+        let details = "Identifier=com.example.app\nTeamIdentifier=ABCD1234EF\n";
+        assert_eq!(
+            parse_team_identifier(details).as_deref(),
+            Some("ABCD1234EF")
+        );
+        assert_eq!(parse_team_identifier("TeamIdentifier=not set"), None);
+    }
+
+    #[test]
+    fn parses_developer_from_signature_and_copyright() {
+        let details = "Identifier=com.acme.app\n\
+            Authority=Developer ID Application: Acme Software, Inc. (ABCD1234EF)\n\
+            Authority=Developer ID Certification Authority\n";
+        assert_eq!(
+            parse_developer_authority(details).as_deref(),
+            Some("Acme Software, Inc.")
+        );
+        assert_eq!(parse_developer_authority("Signature=adhoc"), None);
+
+        let holder = |s| parse_copyright_holder(s);
+        assert_eq!(
+            holder("© 2024 Acme Inc. All rights reserved.").as_deref(),
+            Some("Acme Inc")
+        );
+        assert_eq!(
+            holder("Copyright © 2015-2024 Maria Silva").as_deref(),
+            Some("Maria Silva")
+        );
+        assert_eq!(
+            holder("Copyright (c) 2020, Foo Labs").as_deref(),
+            Some("Foo Labs")
+        );
+        assert_eq!(holder("Feito com carinho"), None);
+    }
+
+    #[test]
+    fn group_containers_match_team_id_prefix() {
+        let tmp = TempDir::new("group-team-id");
+        let groups = tmp.0.join("Library/Group Containers");
+        touch(&groups.join("ABCD1234EF.com.vendor.shared/data"));
+        touch(&groups.join("ZZZZ1234EF.com.vendor.shared/data"));
+
+        let config = Config {
+            team_id: Some("ABCD1234EF".to_string()),
+            ..Default::default()
+        };
+        let found: Vec<_> =
+            find_related_files_in(&tmp.0, "Example", Some("com.example.app"), &config)
+                .into_iter()
+                .map(|f| f.path)
+                .collect();
+        assert_eq!(found, vec![groups.join("ABCD1234EF.com.vendor.shared")]);
+    }
+
+    #[test]
+    fn group_containers_prefer_team_id_over_app_name() {
+        let tmp = TempDir::new("group-prefer-team");
+        let groups = tmp.0.join("Library/Group Containers");
+        touch(&groups.join("ABCD1234EF.com.vendor.shared/data"));
+        touch(&groups.join("group.com.example.app/data"));
+        touch(&groups.join("ZZZZ1234EF.example.cache/data"));
+
+        let find = |id, team| find_group_containers_in(&tmp.0, "Example", Some(id), team);
+        assert_eq!(
+            find("com.example.app", "ABCD1234EF"),
+            [
+                groups.join("ABCD1234EF.com.vendor.shared"),
+                groups.join("group.com.example.app")
+            ]
+        );
+        // Sem nenhum container do Team ID, cai na busca pelo nome.
+        assert_eq!(
+            find("com.other.app", "QQQQ1234EF"),
+            [
+                groups.join("ZZZZ1234EF.example.cache"),
+                groups.join("group.com.example.app")
+            ]
+        );
+    }
+
+    #[test]
+    fn orphaned_containers_skip_installed_apps_and_extensions() {
+        let tmp = TempDir::new("orphaned-containers");
+        for id in [
+            "com.example.app",
+            "com.example.app.ShareExtension",
+            "com.example.removed",
+            "com.apple.Notes",
+        ] {
+            fs::create_dir_all(tmp.0.join(id)).unwrap();
+        }
+        let installed = HashSet::from(["com.example.App".to_string()]);
+
+        let orphans = orphaned_containers_in(&tmp.0, &installed);
+        assert_eq!(
+            orphans,
+            vec![(
+                tmp.0.join("com.example.removed"),
+                "com.example.removed".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn disk_savings_match_the_plan() {
+        let tmp = TempDir::new("disk-savings");
+        let mut foo = app("EstimateFixture", Some("com.example.estimate"));
+        foo.path = tmp.0.join("Applications/EstimateFixture.app");
+        foo.size = 100;
+        touch(&foo.path.join("Contents/MacOS/EstimateFixture"));
+        fs::create_dir_all(tmp.0.join("Library/Caches/com.example.estimate")).unwrap();
+        fs::write(
+            tmp.0.join("Library/Caches/com.example.estimate/data"),
+            [0u8; 40],
+        )
+        .unwrap();
+        fs::create_dir_all(tmp.0.join("Library/Preferences")).unwrap();
+        fs::write(
+            tmp.0.join("Library/Preferences/com.example.estimate.plist"),
+            [0u8; 2],
+        )
+        .unwrap();
+        let mut bar = app("EstimateOther", Some("com.example.other"));
+        bar.size = 50;
+
+        let savings = estimate_disk_savings_in(&tmp.0, &[&foo, &bar]);
+        assert_eq!(savings.total_app_bytes, 150);
+        assert_eq!(savings.total_residual_bytes, 42);
+        assert_eq!(savings.total_bytes, 192);
+
+        let opts = RemovalOptions::default();
+        let related = plan_related_files_in(
+            &tmp.0,
+            &foo.path,
+            "EstimateFixture",
+            foo.bundle_id.as_deref(),
+            &opts,
+            &Config::default(),
+        );
+        let plan = RemovalPlan {
+            app_path: foo.path.clone(),
+            bundle_id: foo.bundle_id.clone(),
+            app_size: foo.size,
+            total_bytes: foo.size + related.iter().map(|f| f.size).sum::<u64>(),
+            related,
+            warnings: Vec::new(),
+        };
+        assert_eq!(
+            DiskSavingsEstimate::from(&plan),
+            estimate_disk_savings_in(&tmp.0, &[&foo])
+        );
+    }
+
+    #[test]
+    fn plan_removal_at_then_execute_plan_removes_everything() {
+        let tmp = TempDir::new("plan-execute");
+        let app = tmp.0.join("PlanTestFixture.app");
+        touch(&app.join("Contents/MacOS/PlanTestFixture"));
+        let cache = tmp.0.join("Caches/com.example.plantest");
+        touch(&cache.join("data"));
+
+        let opts = RemovalOptions::default();
+        let mut plan = plan_removal_at(&app, &opts, &Config::default()).unwrap();
+        plan.related = vec![RelatedFile {
+            path: cache.clone(),
+            category: RelatedFileCategory::Cache,
+            size: dir_size(&cache).unwrap(),
+            requires_elevation: false,
+            contents: Vec::new(),
+        }];
+
+        let events = std::sync::Mutex::new(Vec::new());
+        let result = execute_plan(&plan, &opts, |e| events.lock().unwrap().push(e)).unwrap();
+
+        assert!(result.is_success());
+        assert_eq!(result.removed, vec![app.clone(), cache.clone()]);
+        assert!(!app.exists() && !cache.exists());
+        let removed = events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|e| matches!(e, RemovalProgress::Removed { .. }))
+            .count();
+        assert_eq!(removed, 2);
+    }
+
+    #[test]
+    fn cookies_prefer_bundle_id_over_app_name() {
+        let tmp = TempDir::new("cookies");
+        let cookies = tmp.0.join("Library/Cookies");
+        touch(&cookies.join("Bar.binarycookies"));
+        touch(&cookies.join("com.vendor.bar.binarycookies"));
+        touch(&cookies.join("Bar Helper.binarycookies"));
+
+        let find = |id| find_cookie_store_in(&tmp.0, "Bar", id);
+        let by_id = cookies.join("com.vendor.bar.binarycookies");
+        assert_eq!(find(Some("com.vendor.bar")), Some(by_id));
+        assert_eq!(
+            find(Some("com.vendor.other")),
+            Some(cookies.join("Bar.binarycookies"))
+        );
+    }
+
+    #[test]
+    fn hsts_hosts_match_the_bundle_id_domain() {
+        assert_eq!(
+            bundle_id_domain("com.Example.app").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(bundle_id_domain("com.apple.Safari"), None);
+        assert_eq!(bundle_id_domain("localapp"), None);
+
+        let hsts = serde_json::json!({
+            "com.apple.CFNetwork.defaultStorageSession": {
+                "example.com": { "Include Subdomains": true },
+                "api.example.com": {},
+                "notexample.com": {},
+            },
+            "HSTS Content Version": 1,
+        });
+        assert_eq!(
+            hsts_hosts_in(&hsts, "example.com"),
+            ["api.example.com", "example.com"]
+        );
+
+        let tmp = TempDir::new("hsts-shared");
+        let cookies = tmp.0.join("Library/Cookies");
+        fs::create_dir_all(&cookies).unwrap();
+        fs::write(
+            cookies.join("HSTS.plist"),
+            "<plist version=\"1.0\"><dict>\
+             <key>mail.google.com</key><dict/>\
+             <key>chrome.google.com</key><dict/>\
+             <key>other.github.io</key><dict/>\
+             </dict></plist>",
+        )
+        .unwrap();
+        assert_eq!(
+            find_hsts_entries_in(&tmp.0, "com.google.Chrome"),
+            ["chrome.google.com"]
+        );
+        assert!(find_hsts_entries_in(&tmp.0, "io.github.foo").is_empty());
+    }
+
+    #[test]
+    fn cloudkit_data_is_listed_inside_the_container() {
+        let tmp = TempDir::new("cloudkit-nested");
+        let container = tmp.0.join("Library/Containers/com.foo.bar");
+        let cloudkit = container.join("Data/Library/Application Support/CloudKit");
+        fs::create_dir_all(&cloudkit).unwrap();
+        fs::write(cloudkit.join("db.sqlite"), [0u8; 30]).unwrap();
+        fs::write(container.join("Data/other"), [0u8; 5]).unwrap();
+
+        let found = found_paths(&tmp.0, "Foo Bar", Some("com.foo.bar"));
+        assert!(found.contains(&container));
+        assert!(found.contains(&cloudkit));
+
+        let related =
+            find_related_files_in(&tmp.0, "Foo Bar", Some("com.foo.bar"), &Config::default());
+        let size_of = |path: &Path| related.iter().find(|f| f.path == path).unwrap().size;
+        assert_eq!(size_of(&cloudkit), 30);
+        assert_eq!(size_of(&container), 5);
+
+        // O CloudKit sai antes do container; mantido, o container fica tambem.
+        let order: Vec<_> = removal_order(&related)
+            .iter()
+            .map(|f| f.path.clone())
+            .collect();
+        let at = |path: &PathBuf| order.iter().position(|p| p == path).unwrap();
+        assert!(at(&cloudkit) < at(&container));
+        let kept =
+            filter_related_files_by(related, |f| f.category != RelatedFileCategory::CloudKit);
+        assert!(kept
+            .iter()
+            .all(|f| f.path != container && f.path != cloudkit));
+    }
+
+    #[test]
+    fn restore_puts_back_outer_folders_first() {
+        let tmp = TempDir::new("restore-nested");
+        let trash = tmp.0.join(".Trash");
+        let container = tmp.0.join("Containers/com.foo");
+        let cloudkit = container.join("Data/CloudKit");
+        touch(&cloudkit.join("db.sqlite"));
+        touch(&container.join("Data/other"));
+        let trashed = [&cloudkit, &container]
+            .map(|path| (path.clone(), move_to_trash_in(path, &trash).unwrap()));
+
+        let record = RemovalRecord {
+            app_name: "Foo".to_string(),
+            bundle_id: Some("com.foo".to_string()),
+            timestamp: 0,
+            strategy: RemovalStrategy::MoveToTrash,
+            removed: vec![cloudkit.clone(), container.clone()],
+            trashed: trashed.to_vec(),
+        };
+        let result = restore_from_trash_in(&record, &trash).unwrap();
+
+        assert!(result.conflicts.is_empty() && result.failed.is_empty());
+        assert!(cloudkit.join("db.sqlite").exists());
+        assert!(container.join("Data/other").exists());
+    }
+
+    #[test]
+    fn plan_warnings_detect_mas_brew_extensions_and_icloud() {
+        let tmp = TempDir::new("plan-warnings");
+        let app = tmp.0.join("Applications/Foo Bar.app");
+        touch(&app.join("Contents/_MASReceipt/receipt"));
+        touch(&app.join("Contents/Library/SystemExtensions/com.foo.ext.systemextension/x"));
+        fs::create_dir_all(tmp.0.join("Caskroom/foo-bar/1.0")).unwrap();
+        fs::create_dir_all(tmp.0.join("Library/Mobile Documents/iCloud~com~foo~bar")).unwrap();
+        touch(&tmp.0.join("PrivilegedHelperTools/com.foo.bar.helper"));
+        let support = tmp
+            .0
+            .join("Library/Containers/com.foo.bar/Data/Library/Application Support");
+        touch(&support.join("CloudKit/db.sqlite"));
+
+        let warnings = plan_warnings_in(
+            &app,
+            Some("com.foo.bar"),
+            &tmp.0,
+            &[&tmp.0.join("Caskroom")],
+            &tmp.0.join("PrivilegedHelperTools"),
+            &[],
+        );
+        assert_eq!(
+            warnings,
+            vec![
+                Warning::MasApp,
+                Warning::HomebrewManaged {
+                    cask: "foo-bar".to_string()
+                },
+                Warning::SystemExtensionPresent,
+                Warning::PrivilegedHelperPresent,
+                Warning::ICloudDataPresent,
+                Warning::CloudKitDataPresent,
+            ]
+        );
+
+        let plain = tmp.0.join("Applications/Other.app");
+        fs::create_dir_all(&plain).unwrap();
+        assert!(plan_warnings_in(&plain, Some("com.other"), &tmp.0, &[], &tmp.0, &[]).is_empty());
+    }
+
+    #[test]
+    fn window_restoration_is_cleared_only_when_prefs_remain() {
+        let file = |path: &str, category| RelatedFile {
+            path: PathBuf::from(path),
+            category,
+            size: 0,
+            requires_elevation: false,
+            contents: Vec::new(),
+        };
+        let state = file(
+            "/Users/me/Library/Saved Application State/com.foo.savedState",
+            RelatedFileCategory::SavedState,
+        );
+        let prefs = file(
+            "/Users/me/Library/Preferences/com.foo.plist",
+            RelatedFileCategory::Preferences,
+        );
+        let other_prefs = file(
+            "/Users/me/Library/Preferences/com.foo.helper.plist",
+            RelatedFileCategory::Preferences,
+        );
+        assert!(needs_window_restoration_cleared("com.foo", &[&state]));
+        assert!(needs_window_restoration_cleared(
+            "com.foo",
+            &[&state, &other_prefs]
+        ));
+        assert!(!needs_window_restoration_cleared(
+            "com.foo",
+            &[&state, &prefs]
+        ));
+        assert!(!needs_window_restoration_cleared(
+            "com.foo",
+            &[&other_prefs]
+        ));
+    }
+
+    #[test]
+    fn sort_by_size_desc_puts_biggest_first() {
+        let file = |path: &str, size| RelatedFile {
+            path: PathBuf::from(path),
+            category: RelatedFileCategory::Cache,
+            size,
+            requires_elevation: false,
+            contents: Vec::new(),
+        };
+        let mut files = vec![
+            file("/b", 10),
+            file("/c", 500),
+            file("/a", 10),
+            file("/d", 0),
+        ];
+        sort_by_size_desc(&mut files);
+        let order: Vec<_> = files.iter().map(|f| f.path.to_str().unwrap()).collect();
+        assert_eq!(order, ["/c", "/a", "/b", "/d"]);
+    }
+
+    #[test]
+    fn parses_size_strings() {
+        assert_eq!(parse_size_string("500MB").unwrap(), 500 * 1024 * 1024);
+        assert_eq!(parse_size_string("1.5GB").unwrap(), 1536 * 1024 * 1024);
+        assert_eq!(parse_size_string("1,5 gb").unwrap(), 1536 * 1024 * 1024);
+        assert_eq!(parse_size_string("2k").unwrap(), 2048);
+        assert_eq!(parse_size_string("42").unwrap(), 42);
+        for bad in ["", "GB", "1.5XB", "-1GB", "1..5MB"] {
+            assert!(
+                matches!(parse_size_string(bad), Err(RemoverError::InvalidSize(_))),
+                "{:?}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn app_versions_compare_numerically() {
+        let v = |s| AppVersion::parse(s).unwrap();
+        assert_eq!(
+            v("2.10.3"),
+            AppVersion {
+                major: 2,
+                minor: 10,
+                patch: 3,
+                pre: None
+            }
+        );
+        assert_eq!(v("v1.2-beta1").pre.as_deref(), Some("beta1"));
+        assert_eq!(v("3.1b4").pre.as_deref(), Some("b4"));
+        assert_eq!(v("2.1"), v("2.1.0"));
+        assert!(v("2.10") > v("2.9"));
+        assert!(v("1.0.0-rc1") < v("1.0.0"));
+        assert!(v("1.0.0-beta") < v("1.0.0-rc"));
+        assert_eq!(AppVersion::parse("build 42"), None);
+        assert_eq!(v("1.4.2-beta").to_string(), "1.4.2-beta");
+    }
+
+    #[test]
+    fn finds_custom_spelling_dictionaries_with_restart_note() {
+        let tmp = TempDir::new("spelling");
+        let spelling = tmp.0.join("Library/Spelling");
+        touch(&spelling.join("Scrivener.dic"));
+        touch(&spelling.join("LocalDictionary"));
+        touch(&spelling.join("pt_BR"));
+
+        let found = find_related_files_in(&tmp.0, "Scrivener", None, &Config::default());
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, spelling.join("Scrivener.dic"));
+        assert_eq!(found[0].category, RelatedFileCategory::Spelling);
+        assert_eq!(removal_notes(&found).len(), 1);
+    }
+
+    #[test]
+    fn move_to_trash_numbers_conflicts_like_finder() {
+        let tmp = TempDir::new("trash");
+        let trash = tmp.0.join(".Trash");
+        let mut dests = Vec::new();
+        for _ in 0..3 {
+            let app = tmp.0.join("Foo.app");
+            touch(&app.join("Contents/Info.plist"));
+            dests.push(move_to_trash_in(&app, &trash).unwrap());
+            assert!(!app.exists());
+        }
+        assert_eq!(
+            dests,
+            [
+                trash.join("Foo.app"),
+                trash.join("Foo 2.app"),
+                trash.join("Foo 3.app")
+            ]
+        );
+        assert!(trash.join("Foo 3.app/Contents/Info.plist").exists());
+
+        let readme = tmp.0.join("LEIAME");
+        touch(&readme);
+        touch(&trash.join("LEIAME"));
+        assert_eq!(
+            move_to_trash_in(&readme, &trash).unwrap(),
+            trash.join("LEIAME 2")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn move_to_trash_copies_across_volumes() {
+        let tmp = TempDir::new("trash-exdev");
+        let trash = tmp.0.join(".Trash");
+        let app = tmp.0.join("Volume/Foo.app");
+        touch(&app.join("Contents/MacOS/Foo"));
+        std::os::unix::fs::symlink("MacOS/Foo", app.join("Contents/Current")).unwrap();
+        let exdev = |_: &Path, _: &Path| Err(io::Error::from(io::ErrorKind::CrossesDevices));
+
+        let dest = move_to_trash_with(&app, &trash, exdev).unwrap();
+        assert_eq!(dest, trash.join("Foo.app"));
+        assert!(!app.exists());
+        assert!(dest.join("Contents/MacOS/Foo").is_file());
+        assert_eq!(
+            fs::read_link(dest.join("Contents/Current")).unwrap(),
+            Path::new("MacOS/Foo")
+        );
+
+        // Outros erros do `rename` continuam sendo erros, sem copia parcial.
+        touch(&app.join("x"));
+        let denied = |_: &Path, _: &Path| Err(io::Error::from(io::ErrorKind::PermissionDenied));
+        assert!(move_to_trash_with(&app, &trash, denied).is_err());
+        assert!(app.exists() && !trash.join("Foo 2.app").exists());
+    }
+
+    #[test]
+    fn finds_pending_crash_reports_by_app_name() {
+        let tmp = TempDir::new("crash-reporter");
+        let reports = tmp.0.join("Library/Application Support/CrashReporter");
+        touch(&reports.join("Tower_0C6E4C1A-1111-2222-3333-444455556666.plcrash"));
+        touch(&reports.join("Other_0C6E4C1A-1111-2222-3333-444455556666.plcrash"));
+
+        let found = find_related_files_in(&tmp.0, "Tower", None, &Config::default());
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].category, RelatedFileCategory::CrashLog);
+        assert!(found[0].path.starts_with(&reports));
+    }
+
+    #[test]
+    fn url_schemes_are_collected_from_all_url_types() {
+        let types = serde_json::json!([
+            { "CFBundleURLName": "com.tinyspeck.slack", "CFBundleURLSchemes": ["slack"] },
+            { "CFBundleURLSchemes": ["slack-dev", "slack"] },
+            { "CFBundleURLName": "sem esquemas" },
+        ]);
+        assert_eq!(url_schemes_in(&types), ["slack", "slack-dev"]);
+        assert!(url_schemes_in(&serde_json::json!("invalido")).is_empty());
+    }
+
+    #[test]
+    fn document_types_read_name_extensions_and_mime_types() {
+        let types = serde_json::json!([
+            {
+                "CFBundleTypeName": "Markdown",
+                "CFBundleTypeExtensions": ["md", "markdown"],
+                "CFBundleTypeMIMETypes": ["text/markdown"],
+            },
+            { "LSItemContentTypes": ["public.plain-text"] },
+            { "CFBundleTypeRole": "Viewer" },
+        ]);
+        assert_eq!(
+            document_types_in(&types),
+            [
+                DocumentType {
+                    name: "Markdown".to_string(),
+                    extensions: vec!["md".to_string(), "markdown".to_string()],
+                    mime_types: vec!["text/markdown".to_string()],
+                },
+                DocumentType {
+                    name: "public.plain-text".to_string(),
+                    extensions: Vec::new(),
+                    mime_types: Vec::new(),
+                },
+            ]
+        );
+    }
+
+    #[cfg(feature = "native-plist")]
+    #[test]
+    fn launch_services_handlers_of_the_app_are_removed() {
+        let handler = |kind: &str, role: &str, target: &str| {
+            let pairs = [(kind, "x"), (role, target)];
+            plist::Value::Dictionary(
+                pairs
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), plist::Value::from(*v)))
+                    .collect(),
+            )
+        };
+        let safari = handler(
+            "LSHandlerContentType",
+            "LSHandlerRoleAll",
+            "com.apple.Safari",
+        );
+        let handlers = vec![
+            handler(
+                "LSHandlerURLScheme",
+                "LSHandlerRoleAll",
+                "com.tinyspeck.slack",
+            ),
+            safari.clone(),
+            handler(
+                "LSHandlerContentType",
+                "LSHandlerRoleViewer",
+                "/Applications/Slack.app",
+            ),
+        ];
+        let mut prefs = plist::Value::Dictionary(
+            [("LSHandlers".to_string(), plist::Value::Array(handlers))]
+                .into_iter()
+                .collect(),
+        );
+
+        let app = Path::new("/Applications/Slack.app");
+        let removed = remove_launch_services_handlers(&mut prefs, app, Some("com.tinyspeck.slack"));
+        assert_eq!(removed, 2);
+        let remaining = prefs.as_dictionary().unwrap()["LSHandlers"]
+            .as_array()
+            .unwrap();
+        assert_eq!(remaining, &[safari]);
+    }
+
+    #[test]
+    fn privacy_keys_are_listed_without_values() {
+        let keys = privacy_keys_in([
+            "CFBundleIdentifier",
+            "NSMicrophoneUsageDescription",
+            "NSCameraUsageDescription",
+            "NSLocationWhenInUseUsageDescription",
+            "NSCameraUsageDescription",
+            "NSHumanReadableCopyright",
+        ]);
+        assert_eq!(
+            keys,
+            [
+                "NSCameraUsageDescription",
+                "NSLocationWhenInUseUsageDescription",
+                "NSMicrophoneUsageDescription"
+            ]
+        );
+        let names: Vec<_> = keys.iter().map(|k| privacy_resource_name(k)).collect();
+        assert_eq!(names, ["Camera", "Localizacao", "Microfone"]);
+        assert_eq!(privacy_resource_name("NSFooBarUsageDescription"), "FooBar");
+    }
+
+    #[test]
+    fn finds_diagnostic_captures_by_extension() {
+        let tmp = TempDir::new("diagnostics");
+        let diag = tmp.0.join("Library/Diagnostics");
+        touch(&diag.join("iStat Menus_2024-01-15.diag"));
+        touch(&diag.join("iStat Menus_2024-02-01.diagzip"));
+        touch(&diag.join("iStat Menus notes.txt"));
+
+        let config = Config::default();
+        let mut found: Vec<_> = find_related_files_in(&tmp.0, "iStat Menus", None, &config)
+            .into_iter()
+            .inspect(|f| assert_eq!(f.category, RelatedFileCategory::Diagnostics))
+            .map(|f| f.path)
+            .collect();
+        found.sort();
+        assert_eq!(
+            found,
+            [
+                diag.join("iStat Menus_2024-01-15.diag"),
+                diag.join("iStat Menus_2024-02-01.diagzip")
+            ]
+        );
+    }
+
+    fn app(name: &str, id: Option<&str>) -> AppInfo {
+        AppInfo {
+            name: name.to_string(),
+            path: PathBuf::from(format!("/Applications/{}.app", name)),
+            size: 0,
+            bundle_id: id.map(str::to_string),
+            is_running: false,
+            is_system: false,
+            installed_date: None,
+            version: None,
+            version_parsed: None,
+            privacy_keys: Vec::new(),
+            copyright: None,
+        }
+    }
+
+    #[test]
+    fn search_modes_compare_normalized_names() {
+        use SearchMatchMode::*;
+        assert!(Substring.matches("Google Chrome", "chrome"));
+        assert!(!Exact.matches("Google Chrome", "chrome"));
+        assert!(Exact.matches("Google Chrome", "google chrome"));
+        assert!(Exact.matches("Cafe\u{301}", "Caf\u{e9}.app"));
+        assert!(Fuzzy.matches("Google Chrome", "gchr"));
+        assert!(!Fuzzy.matches("Google Chrome", "rhc"));
+    }
+
+    #[test]
+    fn search_matches_localized_name() {
+        let tmp = TempDir::new("search-localized");
+        let code = tmp.0.join("Code.app");
+        fs::create_dir_all(code.join("Contents")).unwrap();
+        fs::write(
+            code.join("Contents/Info.plist"),
+            "<plist version=\"1.0\"><dict>\n\
+             <key>CFBundleDisplayName</key><string>Visual Studio Code</string>\n\
+             </dict></plist>\n",
+        )
+        .unwrap();
+        touch(&tmp.0.join("Xcode.app/Contents/MacOS/Xcode"));
+        let dirs = [tmp.0.clone()];
+        let search = |query, mode| search_apps_in(&dirs, query, mode, false);
+
+        assert_eq!(
+            get_localized_name(&code).as_deref(),
+            Some("Visual Studio Code")
+        );
+        assert_eq!(
+            search("visual studio code", SearchMatchMode::Exact),
+            std::slice::from_ref(&code)
+        );
+        assert_eq!(
+            search("code", SearchMatchMode::Exact),
+            std::slice::from_ref(&code)
+        );
+        assert_eq!(
+            search("studio", SearchMatchMode::Substring),
+            std::slice::from_ref(&code)
+        );
+        assert_eq!(search("vsc", SearchMatchMode::Fuzzy), [code]);
+        assert_eq!(search("code", SearchMatchMode::Substring).len(), 2);
+    }
+
+    #[test]
+    fn reads_boolean_entitlements() {
+        let xml = "<plist version=\"1.0\"><dict>\n\
+            <key>com.apple.security.app-sandbox</key>\n\t<true/>\n\
+            <key>com.apple.security.network.client</key><false/>\n\
+            </dict></plist>";
+        assert!(entitlement_enabled(xml, "com.apple.security.app-sandbox"));
+        assert!(!entitlement_enabled(
+            xml,
+            "com.apple.security.network.client"
+        ));
+        assert!(!entitlement_enabled(
+            xml,
+            "com.apple.security.files.user-selected.read-only"
+        ));
+    }
+
+    #[test]
+    fn app_info_displays_name_and_size() {
+        let mut notes = app("Notes", None);
+        notes.size = 44_354_765;
+        assert_eq!(notes.to_string(), "Notes (42.3 MB)");
+    }
+
+    #[test]
+    fn app_infos_compare_by_bundle_path() {
+        let mut moved = app("Notes", Some("com.example.notes"));
+        moved.path = PathBuf::from("/Users/me/Applications/Notes.app");
+        let mut rescanned = app("Notes", Some("com.example.notes"));
+        rescanned.size = 42;
+        let set: HashSet<AppInfo> = [app("Notes", Some("com.example.notes")), moved, rescanned]
+            .into_iter()
+            .collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn compares_minimum_system_version_with_current_os() {
+        assert!(parse_os_version("13.0") > parse_os_version("12.6"));
+        assert!(parse_os_version("14.0.1") > parse_os_version("14.0"));
+        assert_eq!(parse_os_version("14.0"), Some((14, 0, 0)));
+
+        let tmp = TempDir::new("min-system-version");
+        let app = tmp.0.join("Foo.app");
+        let plist = app.join("Contents/Info.plist");
+        fs::create_dir_all(plist.parent().unwrap()).unwrap();
+        // Sem LSMinimumSystemVersion o app e considerado compativel.
+        fs::write(&plist, "<plist version=\"1.0\"><dict></dict></plist>\n").unwrap();
+        assert!(is_compatible_with(&app, (10, 0, 0)));
+
+        fs::write(
+            &plist,
+            "<plist version=\"1.0\"><dict>\n\
+             <key>LSMinimumSystemVersion</key><string>13.0</string>\n\
+             </dict></plist>\n",
+        )
+        .unwrap();
+        assert!(!is_compatible_with(&app, (12, 6, 0)));
+        assert!(is_compatible_with(&app, (13, 0, 0)));
+        assert!(is_compatible_with(&app, (14, 0, 1)));
+    }
+
+    #[test]
+    fn compares_installed_bundle_against_expected() {
+        let tmp = TempDir::new("compare-installed");
+        let mut foo = app("Foo", Some("com.example.foo"));
+        foo.path = tmp.0.join(foo.path.file_name().unwrap());
+        let id = foo.bundle_id.as_deref().unwrap();
+
+        // Bundle ausente: nao ha Info.plist para conferir.
+        assert_eq!(
+            compare_installed_vs_expected(&foo.path, id, "2.0"),
+            ComparisonResult::BundleIdMismatch
+        );
+
+        fs::create_dir_all(foo.path.join("Contents/MacOS")).unwrap();
+        fs::write(
+            foo.path.join("Contents/Info.plist"),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<plist version=\"1.0\"><dict>\n\
+             <key>CFBundleIdentifier</key><string>com.example.foo</string>\n\
+             <key>CFBundleShortVersionString</key><string>2.0</string>\n\
+             </dict></plist>\n",
+        )
+        .unwrap();
+        assert_eq!(
+            compare_installed_vs_expected(&foo.path, "com.example.bar", "2.0"),
+            ComparisonResult::BundleIdMismatch
+        );
+        assert_eq!(
+            compare_installed_vs_expected(&foo.path, id, "2.1"),
+            ComparisonResult::VersionMismatch {
+                found: "2.0".to_string(),
+                expected: "2.1".to_string()
+            }
+        );
+        // `2.0` = `2.0.0`; o que falta agora e o executavel.
+        assert_eq!(
+            compare_installed_vs_expected(&foo.path, id, "2.0.0"),
+            ComparisonResult::MissingExecutable
+        );
+
+        // Um arquivo extra em Contents/MacOS nao substitui o executavel.
+        touch(&foo.path.join("Contents/MacOS/Helper"));
+        assert_eq!(
+            compare_installed_vs_expected(&foo.path, id, "2.0"),
+            ComparisonResult::MissingExecutable
+        );
+        touch(&foo.path.join("Contents/MacOS/Foo"));
+        assert_ne!(
+            compare_installed_vs_expected(&foo.path, id, "2.0"),
+            ComparisonResult::MissingExecutable
+        );
+    }
+
+    #[test]
+    fn duplicate_bundle_ids_map_to_app_indices() {
+        let apps = [
+            app("Photoshop", Some("com.adobe.Photoshop")),
+            app("Notes", Some("com.example.notes")),
+            app("Photoshop Cracked", Some("com.adobe.Photoshop")),
+            app("NoPlist", None),
+            app("NoPlist 2", None),
+        ];
+        let dups = find_duplicate_bundle_ids(&apps);
+        assert_eq!(dups.len(), 1);
+        assert_eq!(dups["com.adobe.Photoshop"], vec![0, 2]);
+    }
+
+    #[test]
+    fn other_copies_are_found_by_bundle_id() {
+        let tmp = TempDir::new("other-copies");
+        let plist = |id: &str| {
+            format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<plist version=\"1.0\"><dict>\n\
+                 <key>CFBundleIdentifier</key><string>{}</string>\n</dict></plist>\n",
+                id
+            )
+        };
+        let apps = [
+            ("Photoshop.app", "com.adobe.Photoshop"),
+            ("Adobe/Photoshop Cracked.app", "com.adobe.Photoshop"),
+            ("Notes.app", "com.example.notes"),
+        ];
+        for (bundle, id) in apps {
+            let contents = tmp.0.join(bundle).join("Contents");
+            fs::create_dir_all(&contents).unwrap();
+            fs::write(contents.join("Info.plist"), plist(id)).unwrap();
+        }
+
+        let installed = installed_bundle_ids_in(std::slice::from_ref(&tmp.0));
+        assert_eq!(installed.len(), 3);
+        let photoshop = tmp.0.join("Photoshop.app");
+        assert!(has_other_copy_installed(
+            &installed,
+            &photoshop,
+            "com.adobe.Photoshop"
+        ));
+        let notes = tmp.0.join("Notes.app");
+        assert!(!has_other_copy_installed(
+            &installed,
+            &notes,
+            "com.example.notes"
+        ));
+        let cracked = tmp.0.join("Adobe/Photoshop Cracked.app");
+        let without_copy: Vec<_> = installed
+            .into_iter()
+            .filter(|(p, _)| *p != cracked)
+            .collect();
+        assert!(!has_other_copy_installed(
+            &without_copy,
+            &photoshop,
+            "com.adobe.Photoshop"
+        ));
+    }
+
+    #[test]
+    fn permission_report_sorts_plan_paths() {
+        let tmp = TempDir::new("permissions");
+        let app = tmp.0.join("Foo.app");
+        fs::create_dir_all(&app).unwrap();
+        let locked_dir = tmp.0.join("locked");
+        let locked = locked_dir.join("com.foo.cache");
+        touch(&locked);
+        let file = |path: PathBuf| RelatedFile {
+            path,
+            category: RelatedFileCategory::Cache,
+            size: 0,
+            requires_elevation: false,
+            contents: Vec::new(),
+        };
+        let plan = RemovalPlan {
+            app_path: app.clone(),
+            bundle_id: None,
+            app_size: 0,
+            related: vec![
+                file(tmp.0.join("gone")),
+                file(locked.clone()),
+                file(PathBuf::from("/Library/Application Support")),
+            ],
+            total_bytes: 0,
+            warnings: Vec::new(),
+        };
+
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&locked_dir, fs::Permissions::from_mode(0o555)).unwrap();
+        let report = check_removal_permissions(&plan);
+        fs::set_permissions(&locked_dir, fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert_eq!(report.missing, [tmp.0.join("gone")]);
+        assert_eq!(
+            report.requires_sudo,
+            [PathBuf::from("/Library/Application Support")]
+        );
+        assert!(report.has_problems());
+        // root ignora as permissoes de escrita
+        if unsafe { libc::geteuid() } == 0 {
+            assert_eq!(report.writable, [app, locked]);
+        } else {
+            assert_eq!(report.writable, [app]);
+            assert_eq!(report.read_only, [locked]);
+        }
+    }
+
+    #[test]
+    fn removal_history_is_capped_and_newest_first() {
+        let tmp = TempDir::new("history");
+        let file = tmp.0.join("share/history.json");
+        let record = |n: u64| RemovalRecord {
+            app_name: format!("App{}", n),
+            bundle_id: None,
+            timestamp: n,
+            strategy: RemovalStrategy::MoveToTrash,
+            removed: vec![PathBuf::from(format!("/Applications/App{}.app", n))],
+            trashed: Vec::new(),
+        };
+        for n in 1..=5 {
+            append_removal_record_in(&file, record(n), 3).unwrap();
+        }
+        let history = get_removal_history_in(&file);
+        assert_eq!(history, [record(5), record(4), record(3)]);
+
+        fs::write(&file, "nao e json").unwrap();
+        assert!(get_removal_history_in(&file).is_empty());
+    }
+
+    #[test]
+    fn formats_timestamps_as_utc_dates() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00");
+        assert_eq!(format_timestamp(1_709_210_096), "2024-02-29 12:34");
+        let secs = parse_date("2031-12-31")
+            .unwrap()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        assert_eq!(format_timestamp(secs), "2031-12-31 00:00");
+    }
+
+    #[test]
+    fn restores_latest_trashed_copy_and_reports_the_rest() {
+        let tmp = TempDir::new("restore-trash");
+        let trash = tmp.0.join(".Trash");
+        let apps = tmp.0.join("Applications");
+        // Uma versao antiga ja estava na Lixeira quando o app foi removido.
+        touch(&trash.join("Foo.app/old"));
+        touch(&apps.join("Foo.app/new"));
+        move_to_trash_in(&apps.join("Foo.app"), &trash).unwrap();
+        touch(&tmp.0.join("Caches/com.foo/data"));
+        move_to_trash_in(&tmp.0.join("Caches/com.foo"), &trash).unwrap();
+        touch(&tmp.0.join("Caches/com.foo/recreated"));
+
+        let record = RemovalRecord {
+            app_name: "Foo".to_string(),
+            bundle_id: Some("com.foo".to_string()),
+            timestamp: 0,
+            strategy: RemovalStrategy::MoveToTrash,
+            removed: vec![
+                apps.join("Foo.app"),
+                tmp.0.join("Caches/com.foo"),
+                tmp.0.join("Logs/Foo"),
+            ],
+            trashed: Vec::new(),
+        };
+        let result = restore_from_trash_in(&record, &trash).unwrap();
+
+        assert_eq!(result.restored, [apps.join("Foo.app")]);
+        assert_eq!(result.conflicts, [tmp.0.join("Caches/com.foo")]);
+        assert_eq!(result.not_found, [tmp.0.join("Logs/Foo")]);
+        assert!(result.failed.is_empty());
+        assert!(apps.join("Foo.app/new").exists());
+        assert!(trash.join("Foo.app/old").exists());
+    }
+
+    #[test]
+    fn restores_same_named_paths_to_their_own_parents() {
+        let tmp = TempDir::new("restore-same-name");
+        let trash = tmp.0.join(".Trash");
+        let cache = tmp.0.join("Caches/com.foo");
+        let container = tmp.0.join("Containers/com.foo");
+        touch(&cache.join("cache-data"));
+        touch(&container.join("container-data"));
+        let trashed = [&cache, &container]
+            .map(|path| (path.clone(), move_to_trash_in(path, &trash).unwrap()));
+
+        let record = RemovalRecord {
+            app_name: "Foo".to_string(),
+            bundle_id: Some("com.foo".to_string()),
+            timestamp: 0,
+            strategy: RemovalStrategy::MoveToTrash,
+            removed: vec![cache.clone(), container.clone()],
+            trashed: trashed.to_vec(),
+        };
+        let result = restore_from_trash_in(&record, &trash).unwrap();
+
+        assert_eq!(result.restored, [cache.clone(), container.clone()]);
+        assert!(cache.join("cache-data").exists());
+        assert!(container.join("container-data").exists());
+    }
+
+    #[test]
+    fn restore_keeps_going_after_a_failed_item() {
+        let tmp = TempDir::new("restore-failed");
+        let trash = tmp.0.join(".Trash");
+        let prefs = tmp.0.join("Preferences/com.foo.plist");
+        let cache = tmp.0.join("Caches/com.foo");
+        touch(&prefs);
+        touch(&cache.join("data"));
+        let trashed =
+            [&prefs, &cache].map(|path| (path.clone(), move_to_trash_in(path, &trash).unwrap()));
+        // Um arquivo no lugar da pasta-pai: o primeiro item nao tem para onde voltar.
+        fs::remove_dir(tmp.0.join("Preferences")).unwrap();
+        fs::write(tmp.0.join("Preferences"), b"x").unwrap();
+
+        let record = RemovalRecord {
+            app_name: "Foo".to_string(),
+            bundle_id: Some("com.foo".to_string()),
+            timestamp: 0,
+            strategy: RemovalStrategy::MoveToTrash,
+            removed: vec![prefs.clone(), cache.clone()],
+            trashed: trashed.to_vec(),
+        };
+        let result = restore_from_trash_in(&record, &trash).unwrap();
+
+        assert!(cache.join("data").exists());
+        assert!(trashed[0].1.exists());
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(result.failed[0].0, prefs);
+        assert_eq!(result.restored, [cache]);
+    }
+
+    #[test]
+    fn parses_btm_dump_and_matches_app_entries() {
+        let dump = "========================\n\
+             Records for UID 501 : 1A2B\n\
+            ========================\n\
+             #1:\n\
+                             UUID: 1111\n\
+                             Name: Foo Helper\n\
+                             Type: login item (0x4)\n\
+                       Identifier: 4.com.foo.app.helper\n\
+                              URL: file:///Applications/Foo%20Pro.app/Contents/Library/LoginItems/Helper.app/\n\
+                Bundle Identifier: com.foo.app.helper\n\
+             #2:\n\
+                             Name: Other\n\
+                       Identifier: 2.com.other\n\
+                              URL: file:///Applications/Other.app/\n";
+        let entries = parse_btm_dump(dump);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "Foo Helper");
+        assert_eq!(entries[0].bundle_id.as_deref(), Some("com.foo.app.helper"));
+        assert!(entries[0].belongs_to("Foo Pro", None));
+        assert!(entries[0].belongs_to("x", Some("com.foo.app")));
+        assert!(!entries[1].belongs_to("Foo Pro", Some("com.foo.app")));
+        assert!(!entries[0].is_app_bundle("Foo Pro"));
+        assert!(entries[1].is_app_bundle("Other"));
+    }
+
+    #[cfg(feature = "native-plist")]
+    #[test]
+    fn login_items_of_the_app_are_removed_from_btm_archive() {
+        use plist::{Uid, Value};
+        let dict = |pairs: Vec<(&str, Value)>| {
+            Value::Dictionary(pairs.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+        };
+        let uid = |n| Value::Uid(Uid::new(n));
+        let archive = dict(vec![
+            ("$archiver", Value::from("NSKeyedArchiver")),
+            (
+                "$objects",
+                Value::Array(vec![
+                    Value::from("$null"),
+                    dict(vec![("NS.objects", Value::Array(vec![uid(2), uid(5)]))]),
+                    dict(vec![("name", uid(3)), ("bookmark", uid(4))]),
+                    Value::from("Foo"),
+                    Value::Data(b"book\0/Applications\0Foo.app\0".to_vec()),
+                    dict(vec![("name", uid(6)), ("bookmark", uid(7))]),
+                    Value::from("MyFoo"),
+                    dict(vec![(
+                        "NS.data",
+                        Value::Data(b"book\0MyFoo.app\0".to_vec()),
+                    )]),
+                ]),
+            ),
+        ]);
+        let tmp = TempDir::new("btm-items");
+        let btm = tmp.0.join("backgrounditems.btm");
+        archive.to_file_binary(&btm).unwrap();
+
+        assert_eq!(count_btm_items(&btm, "Foo", Some("com.foo.app"), false), 1);
+        assert_eq!(Value::from_file(&btm).unwrap(), archive);
+        assert_eq!(count_btm_items(&btm, "Foo", Some("com.foo.app"), true), 1);
+        let edited = Value::from_file(&btm).unwrap();
+        let list = &edited.as_dictionary().unwrap()["$objects"]
+            .as_array()
+            .unwrap()[1];
+        assert_eq!(
+            list.as_dictionary().unwrap()["NS.objects"],
+            Value::Array(vec![uid(5)])
+        );
+        assert_eq!(count_btm_items(&btm, "Foo", Some("com.foo.app"), false), 0);
+        assert_eq!(count_btm_items(&btm, "MyFoo", None, false), 1);
+    }
+
+    #[test]
+    fn finds_sfl_files_referencing_bundle_id() {
+        let tmp = TempDir::new("sfl");
+        let lists = tmp
+            .0
+            .join("Library/Application Support/com.apple.sharedfilelist");
+        let per_app = lists.join("com.apple.LSSharedFileList.ApplicationRecentDocuments");
+        fs::create_dir_all(&per_app).unwrap();
+        touch(&per_app.join("com.foo.app.sfl2"));
+        let shared = lists.join("com.apple.LSSharedFileList.RecentApplications.sfl2");
+        fs::write(shared, b"\0bplist\0com.foo.app\0").unwrap();
+        fs::write(
+            lists.join("com.apple.LSSharedFileList.RecentHosts.sfl2"),
+            b"other",
+        )
+        .unwrap();
+
+        let sfl = find_sfl_files_in(&tmp.0, "com.foo.app");
+        assert_eq!(sfl.len(), 2);
+
+        let related = find_related_files_in(&tmp.0, "Foo", Some("com.foo.app"), &Config::default());
+        let recent: Vec<_> = related
+            .iter()
+            .filter(|f| f.category == RelatedFileCategory::RecentItems)
+            .collect();
+        assert_eq!(recent.len(), 1);
+        assert!(recent[0].path.ends_with("com.foo.app.sfl2"));
+    }
+
+    #[test]
+    fn splits_listxattr_buffer_into_names() {
+        let buf = b"com.apple.quarantine\0com.apple.rootless\0";
+        assert_eq!(
+            split_xattr_names(buf),
+            ["com.apple.quarantine", "com.apple.rootless"]
+        );
+        assert!(split_xattr_names(b"").is_empty());
+    }
+
+    /// Respostas pre-definidas para o modo seguro.
+    struct ScriptedPrompt {
+        confirm: bool,
+        choices: VecDeque<SafeModeChoice>,
+        asked: Vec<PathBuf>,
+    }
+
+    impl SafeModePrompt for ScriptedPrompt {
+        fn confirm_app(&mut self, _app_path: &Path, _size: u64) -> bool {
+            self.confirm
+        }
+
+        fn ask_file(&mut self, path: &Path, _size: u64) -> SafeModeChoice {
+            self.asked.push(path.to_path_buf());
+            self.choices.pop_front().unwrap_or(SafeModeChoice::Quit)
+        }
+    }
+
+    #[test]
+    fn safe_mode_follows_each_answer() {
+        let tmp = TempDir::new("safe-mode");
+        let app = tmp.0.join("Foo.app");
+        touch(&app.join("Contents/Info.plist"));
+        let paths: Vec<PathBuf> = ["a", "b", "c", "d"].iter().map(|n| tmp.0.join(n)).collect();
+        let related: Vec<RelatedFile> = paths
+            .iter()
+            .map(|path| {
+                touch(path);
+                RelatedFile {
+                    path: path.clone(),
+                    category: RelatedFileCategory::Cache,
+                    size: 1,
+                    requires_elevation: false,
+                    contents: Vec::new(),
+                }
+            })
+            .collect();
+        let mut prompt = ScriptedPrompt {
+            confirm: false,
+            choices: [
+                SafeModeChoice::Remove,
+                SafeModeChoice::Skip,
+                SafeModeChoice::Quit,
+            ]
+            .into(),
+            asked: Vec::new(),
+        };
+
+        let cancelled = remove_with_prompt(&app, &related, &mut prompt);
+        assert!(matches!(cancelled, Err(RemoverError::Cancelled)));
+        assert!(app.exists() && prompt.asked.is_empty());
+
+        prompt.confirm = true;
+        let result = remove_with_prompt(&app, &related, &mut prompt).unwrap();
+        assert_eq!(result.removed, [app.clone(), paths[0].clone()]);
+        assert_eq!(result.skipped, &paths[1..]);
+        // Depois do `Quit`, nada mais e perguntado.
+        assert_eq!(prompt.asked, &paths[..3]);
+        assert!(!app.exists() && !paths[0].exists());
+        assert!(paths[1..].iter().all(|p| p.exists()));
+    }
+
+    #[test]
+    fn stdin_prompt_quits_at_end_of_input() {
+        let choice = |input: &str| StdinPrompt::file_choice(&mut input.as_bytes());
+        assert_eq!(choice("s\n"), SafeModeChoice::Skip);
+        assert_eq!(choice("talvez\nREMOVE\n"), SafeModeChoice::Remove);
+        assert_eq!(choice("talvez\n"), SafeModeChoice::Quit);
+        assert_eq!(choice(""), SafeModeChoice::Quit);
+    }
+
+    #[test]
+    fn batch_removal_reports_each_app_in_order() {
+        let names = ["MacAppRemoverMissingA", "MacAppRemoverMissingB"];
+        let events = std::sync::Mutex::new(Vec::new());
+        let results = batch_remove_apps(&names, &RemovalOptions::default(), |e| {
+            events.lock().unwrap().push(e);
+        });
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].app_path, PathBuf::from(names[1]));
+        assert!(results.iter().all(|r| !r.is_success()));
+        let events = events.into_inner().unwrap();
+        assert_eq!(events.len(), 5);
+        assert!(matches!(
+            &events[2],
+            BatchProgress::StartingApp {
+                index: 1,
+                total: 2,
+                ..
+            }
+        ));
+        assert!(matches!(events[4], BatchProgress::AllDone));
+    }
 }
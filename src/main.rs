@@ -1,87 +1,480 @@
-use std::io::{self, Write};
+use std::fmt;
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
 
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, execute, queue};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use mac_app_remover::*;
+use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
+    let _ = NO_COLOR.set(args.iter().any(|a| a == "--no-color"));
 
     match args.get(1).map(|s| s.as_str()) {
-        Some("list") => list_apps(),
+        Some("list") => {
+            let quarantined = args[2..].iter().any(|a| a == "--quarantined");
+            let running = args[2..].iter().any(|a| a == "--running");
+            let recursive = args[2..].iter().any(|a| a == "--recursive");
+            let user_only = args[2..].iter().any(|a| a == "--user-only");
+            let duplicates = args[2..].iter().any(|a| a == "--duplicates");
+            let incompatible = args[2..].iter().any(|a| a == "--incompatible");
+            let installed_after = match args[2..]
+                .iter()
+                .find_map(|a| a.strip_prefix("--installed-after="))
+            {
+                Some(date) => match parse_date(date) {
+                    Some(t) => Some(t),
+                    None => {
+                        eprintln!("Data invalida: \"{}\" (use AAAA-MM-DD)", date);
+                        return;
+                    }
+                },
+                None => None,
+            };
+            let min_size = match args[2..].iter().find_map(|a| a.strip_prefix("--min-size=")) {
+                Some(size) => match parse_size_string(size) {
+                    Ok(bytes) => Some(bytes),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return;
+                    }
+                },
+                None => None,
+            };
+            if duplicates {
+                list_duplicate_bundle_ids(recursive);
+                return;
+            }
+            if incompatible {
+                list_incompatible_apps();
+                return;
+            }
+            list_apps(
+                quarantined,
+                running,
+                recursive,
+                user_only,
+                installed_after,
+                min_size,
+            );
+        }
         Some("remove") => {
-            if let Some(app_name) = args.get(2) {
-                remove_app(app_name);
+            let rest = &args[2..];
+            let safe = rest.iter().any(|a| a == "--safe");
+            let interactive = rest.iter().any(|a| a == "--interactive");
+            let config = Config {
+                search_system_library: rest.iter().any(|a| a == "--system"),
+                verbose: rest.iter().any(|a| a == "--verbose" || a == "-v"),
+                team_id: None,
+                thorough: rest.iter().any(|a| a == "--thorough"),
+            };
+            #[cfg(feature = "log")]
+            if config.verbose {
+                init_stderr_logger();
+            }
+            let opts = RemovalOptions {
+                keep_preferences: rest.iter().any(|a| a == "--keep-prefs"),
+                keep_caches: rest.iter().any(|a| a == "--keep-caches"),
+                rebuild_launch_services: rest.iter().any(|a| a == "--rebuild-launch-services"),
+                strategy: if rest.iter().any(|a| a == "--trash") {
+                    RemovalStrategy::MoveToTrash
+                } else {
+                    RemovalStrategy::PermanentDelete
+                },
+                ..Default::default()
+            };
+            if let Some(app_name) = rest.iter().find(|a| !a.starts_with('-')) {
+                if safe {
+                    remove_app_safe(app_name);
+                } else {
+                    remove_app(app_name, &opts, &config, interactive);
+                }
             } else {
-                eprintln!("Uso: mac-app-remover remove <NomeDoApp>");
+                eprintln!(
+                    "Uso: mac-app-remover remove [--safe] [--keep-prefs] [--keep-caches] [--trash] [--system] [--thorough] [--rebuild-launch-services] [--interactive] [-v] <NomeDoApp>"
+                );
                 eprintln!("Exemplo: mac-app-remover remove \"Google Chrome\"");
             }
         }
+        Some("info") => {
+            if let Some(app_name) = args.get(2).filter(|a| !a.starts_with('-')) {
+                show_app_info(app_name);
+            } else {
+                eprintln!("Uso: mac-app-remover info <NomeDoApp>");
+            }
+        }
+        Some("verify") => {
+            let rest = &args[2..];
+            let value_of = |flag: &str| {
+                rest.iter()
+                    .position(|a| a == flag)
+                    .and_then(|i| rest.get(i + 1))
+            };
+            let app_name = rest.first().filter(|a| !a.starts_with('-'));
+            match (
+                app_name,
+                value_of("--expected-bundle-id"),
+                value_of("--expected-version"),
+            ) {
+                (Some(app_name), Some(bundle_id), Some(version)) => {
+                    verify_app(app_name, bundle_id, version)
+                }
+                _ => eprintln!(
+                    "Uso: mac-app-remover verify <NomeDoApp> --expected-bundle-id <id> --expected-version <versao>"
+                ),
+            }
+        }
+        Some("history") => show_history(),
+        Some("undo") => match args.get(2) {
+            Some(number) => undo_removal(number),
+            None => {
+                eprintln!("Uso: mac-app-remover undo <numero>  (veja 'mac-app-remover history')")
+            }
+        },
         Some("search") => {
-            if let Some(query) = args.get(2) {
-                search_apps(query);
+            let rest = &args[2..];
+            let recursive = rest.iter().any(|a| a == "--recursive");
+            let mode = if rest.iter().any(|a| a == "--exact") {
+                SearchMatchMode::Exact
+            } else if rest.iter().any(|a| a == "--fuzzy") {
+                SearchMatchMode::Fuzzy
+            } else {
+                SearchMatchMode::Substring
+            };
+            if let Some(query) = rest.iter().find(|a| !a.starts_with("--")) {
+                search_apps(query, mode, recursive);
             } else {
-                eprintln!("Uso: mac-app-remover search <termo>");
+                eprintln!("Uso: mac-app-remover search [--recursive] [--exact|--fuzzy] <termo>");
+            }
+        }
+        Some("scan-orphans") => {
+            if args[2..].iter().any(|a| a == "--containers") {
+                scan_orphans_containers();
+            } else {
+                eprintln!("Uso: mac-app-remover scan-orphans --containers");
             }
         }
         _ => print_usage(),
     }
 }
 
+static NO_COLOR: OnceLock<bool> = OnceLock::new();
+
+/// Estilos usados na saida do terminal.
+#[derive(Clone, Copy)]
+enum Style {
+    Ok,
+    Error,
+    Warning,
+    Bold,
+    Path,
+}
+
+impl Style {
+    fn spec(self) -> ColorSpec {
+        let mut spec = ColorSpec::new();
+        match self {
+            Style::Ok => spec.set_fg(Some(Color::Green)),
+            Style::Error => spec.set_fg(Some(Color::Red)),
+            Style::Warning => spec.set_fg(Some(Color::Yellow)),
+            Style::Bold => spec.set_bold(true),
+            Style::Path => spec.set_fg(Some(Color::Cyan)),
+        };
+        spec
+    }
+}
+
+/// Sem cores fora de um terminal, com `--no-color` ou com `NO_COLOR` definido.
+/// `ColorChoice::Auto` ja respeita `NO_COLOR` e `TERM=dumb`, mas nao detecta pipes.
+fn color_choice(is_terminal: bool) -> ColorChoice {
+    if !is_terminal || NO_COLOR.get().copied().unwrap_or(false) {
+        ColorChoice::Never
+    } else {
+        ColorChoice::Auto
+    }
+}
+
+fn write_styled(stream: &mut StandardStream, style: Style, text: impl fmt::Display) {
+    let _ = stream.set_color(&style.spec());
+    let _ = write!(stream, "{}", text);
+    let _ = stream.reset();
+}
+
+/// Escreve `text` no stdout com o estilo dado (sem quebra de linha).
+fn paint(style: Style, text: impl fmt::Display) {
+    let mut out = StandardStream::stdout(color_choice(io::stdout().is_terminal()));
+    write_styled(&mut out, style, text);
+}
+
+/// Igual a `paint`, no stderr.
+fn epaint(style: Style, text: impl fmt::Display) {
+    let mut err = StandardStream::stderr(color_choice(io::stderr().is_terminal()));
+    write_styled(&mut err, style, text);
+}
+
+/// Cabecalho de secao em negrito, seguido de quebra de linha.
+fn header(text: impl fmt::Display) {
+    paint(Style::Bold, text);
+    println!();
+}
+
+/// Com a feature `log`, `--verbose` passa pelo `log`; este logger minimo
+/// imprime as mensagens no stderr.
+#[cfg(feature = "log")]
+fn init_stderr_logger() {
+    struct StderrLogger;
+
+    impl log::Log for StderrLogger {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.level() <= log::max_level()
+        }
+
+        fn log(&self, record: &log::Record) {
+            if self.enabled(record.metadata()) {
+                eprintln!("{}", record.args());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    if log::set_logger(&StderrLogger).is_ok() {
+        log::set_max_level(log::LevelFilter::Info);
+    }
+}
+
 fn print_usage() {
-    println!("=== Mac App Remover ===");
+    header("=== Mac App Remover ===");
     println!();
     println!("Uso:");
     println!("  mac-app-remover list               - Lista todos os aplicativos instalados");
+    println!("      --quarantined                   Apenas apps ainda em quarentena");
+    println!("      --running                       Apenas apps em execucao");
+    println!(
+        "      --recursive                     Inclui subpastas (ex: /Applications/Utilities)"
+    );
+    println!("      --user-only                     Omite apps de sistema (/System/Applications)");
+    println!("      --installed-after=AAAA-MM-DD    Apenas apps instalados depois da data");
+    println!("      --min-size=TAMANHO              Apenas apps a partir do tamanho (ex: 1GB)");
+    println!("      --duplicates                    Apps diferentes com o mesmo bundle ID");
+    println!("      --incompatible                  Apps que exigem um macOS mais novo");
+    println!("  mac-app-remover info <NomeDoApp>    - Mostra detalhes de um aplicativo");
+    println!("  mac-app-remover verify <NomeDoApp> --expected-bundle-id <id> --expected-version <versao>");
+    println!("                                      - Confere bundle ID, versao, executavel e assinatura");
     println!("  mac-app-remover search <termo>      - Busca aplicativos por nome");
-    println!("  mac-app-remover remove <NomeDoApp>  - Remove um aplicativo e seus arquivos residuais");
+    println!("      --recursive                     Inclui subpastas");
+    println!(
+        "      --exact                         Apenas o nome exato (sem diferenciar maiusculas)"
+    );
+    println!("      --fuzzy                         Letras da busca em ordem (ex: gchr)");
+    println!(
+        "  mac-app-remover remove <NomeDoApp>  - Remove um aplicativo e seus arquivos residuais"
+    );
+    println!(
+        "      --safe                          Pergunta individualmente por cada arquivo residual"
+    );
+    println!("      --interactive                   Escolhe pelo numero quais arquivos residuais remover");
+    println!("      --keep-prefs                    Mantem os arquivos de preferencias");
+    println!("      --keep-caches                   Mantem os caches");
+    println!("      --trash                         Move para a Lixeira em vez de apagar");
+    println!("      --system                        Inclui /Library na busca (requer sudo)");
+    println!(
+        "      --thorough                      Tambem busca caches pelo Spotlight (mais lento)"
+    );
+    println!(
+        "      --rebuild-launch-services       Reconstroi o banco do Launch Services (lsregister)"
+    );
+    println!("      -v, --verbose                   Mostra cada diretorio varrido (no stderr)");
+    println!("  --no-color                          Desativa as cores (tambem via NO_COLOR)");
+    println!("  mac-app-remover history             - Lista as remocoes anteriores");
+    println!("  mac-app-remover undo <numero>       - Desfaz uma remocao feita com --trash");
+    println!("  mac-app-remover scan-orphans --containers");
+    println!(
+        "                                      - Lista containers de apps que nao existem mais"
+    );
     println!();
     println!("Exemplos:");
     println!("  mac-app-remover list");
     println!("  mac-app-remover search chrome");
     println!("  mac-app-remover remove \"Google Chrome\"");
+    println!("  mac-app-remover remove --safe \"Google Chrome\"");
+    println!("  mac-app-remover remove --keep-prefs \"Google Chrome\"");
 }
 
-fn list_apps() {
-    let apps = get_installed_apps();
-    println!("=== Aplicativos Instalados ({}) ===\n", apps.len());
+fn list_apps(
+    quarantined: bool,
+    running: bool,
+    recursive: bool,
+    user_only: bool,
+    installed_after: Option<SystemTime>,
+    min_size: Option<u64>,
+) {
+    let mut apps = get_installed_app_infos_opts(running, recursive);
+    if user_only {
+        apps.retain(|app| !app.is_system);
+    }
+    if let Some(threshold) = installed_after {
+        apps.retain(|app| app.installed_date.is_some_and(|date| date > threshold));
+    }
+    if let Some(threshold) = min_size {
+        apps.retain(|app| app.size >= threshold);
+        apps.sort_by_key(|app| std::cmp::Reverse(app.size));
+    }
+    if quarantined {
+        apps.retain(|app| get_quarantine_info(&app.path).is_some());
+    }
+    if running {
+        apps.retain(|app| app.is_running);
+    }
+    header(format!("=== Aplicativos Instalados ({}) ===\n", apps.len()));
     for (i, app) in apps.iter().enumerate() {
-        let name = app.file_stem().unwrap_or_default().to_string_lossy();
-        let size = dir_size(app).unwrap_or(0);
-        println!("  {:>3}. {:<40} {}", i + 1, name, format_size(size));
+        print!("  {:>3}. ", i + 1);
+        paint(Style::Bold, format!("{:<40}", app.name));
+        println!(" {}", format_size(app.size));
     }
 }
 
-fn search_apps(query: &str) {
-    let apps = get_installed_apps();
-    let query_lower = query.to_lowercase();
-    let matches: Vec<_> = apps
-        .iter()
-        .filter(|app| {
-            app.file_stem()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .to_lowercase()
-                .contains(&query_lower)
-        })
-        .collect();
+fn show_app_info(app_name: &str) {
+    let app_path = match find_app(app_name) {
+        Some(p) => p,
+        None => {
+            eprintln!("Aplicativo \"{}\" nao encontrado.", app_name);
+            return;
+        }
+    };
+    let name = app_path.file_stem().unwrap_or_default().to_string_lossy();
+    header(format!("=== {} ===\n", name));
+    print!("  Caminho:    ");
+    paint(Style::Path, app_path.display());
+    println!();
+    if let Some(id) = get_bundle_id(&app_path) {
+        println!("  Bundle ID:  {}", id);
+    }
+    if let Some(version) = get_app_version(&app_path) {
+        println!("  Versao:     {}", version);
+    }
+    if let Some(copyright) = get_app_copyright(&app_path) {
+        println!("  Copyright:  {}", copyright);
+    }
+    println!(
+        "  Tamanho:    {}",
+        format_size(dir_size(&app_path).unwrap_or(0))
+    );
+    if is_app_sandboxed(&app_path) {
+        println!("  Sandbox:    sim (dados em ~/Library/Containers)");
+    }
+    let schemes = get_app_url_schemes(&app_path);
+    if !schemes.is_empty() {
+        let urls: Vec<_> = schemes.iter().map(|s| format!("{}://", s)).collect();
+        println!("  URLs:       {}", urls.join(", "));
+    }
+    let privacy = privacy_resource_names(&get_privacy_keys(&app_path));
+    if !privacy.is_empty() {
+        println!("  Privacidade: {}", privacy.join(", "));
+    }
+    if is_mas_app(&app_path) {
+        match get_mas_app_id(&app_path) {
+            Some(id) => println!("  App Store:  {}", mas_page_url(id)),
+            None => println!("  App Store:  sim (ID nao indexado pelo Spotlight)"),
+        }
+    }
+}
+
+/// Sai com codigo 1 se o app nao conferir, para uso em scripts.
+fn verify_app(app_name: &str, expected_bundle_id: &str, expected_version: &str) {
+    let app_path = match find_app(app_name) {
+        Some(p) => p,
+        None => {
+            eprintln!("Aplicativo \"{}\" nao encontrado.", app_name);
+            std::process::exit(1);
+        }
+    };
+    match compare_installed_vs_expected(&app_path, expected_bundle_id, expected_version) {
+        ComparisonResult::Match => paint(Style::Ok, format!("{}\n", ComparisonResult::Match)),
+        ComparisonResult::BundleIdMismatch => {
+            epaint(Style::Error, "ERRO: ");
+            eprintln!(
+                "Bundle ID diferente: encontrado {}, esperado {}",
+                get_bundle_id(&app_path).as_deref().unwrap_or("(nenhum)"),
+                expected_bundle_id
+            );
+            std::process::exit(1);
+        }
+        other => {
+            epaint(Style::Error, "ERRO: ");
+            eprintln!("{}", other);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn list_duplicate_bundle_ids(recursive: bool) {
+    let apps = get_installed_app_infos_opts(false, recursive);
+    let mut duplicates: Vec<_> = find_duplicate_bundle_ids(&apps).into_iter().collect();
+    duplicates.sort();
+    if duplicates.is_empty() {
+        println!("Nenhum bundle ID duplicado.");
+        return;
+    }
+    header(format!(
+        "=== Bundle IDs duplicados ({}) ===\n",
+        duplicates.len()
+    ));
+    for (id, indices) in &duplicates {
+        paint(Style::Warning, format!("  {}\n", id));
+        for &i in indices {
+            print!("    - ");
+            paint(Style::Path, apps[i].path.display());
+            println!();
+        }
+    }
+}
+
+fn list_incompatible_apps() {
+    let apps = find_incompatible_apps();
+    if apps.is_empty() {
+        println!("Todos os apps sao compativeis com este macOS.");
+        return;
+    }
+    header(format!("=== Apps incompativeis ({}) ===\n", apps.len()));
+    for (i, app) in apps.iter().enumerate() {
+        print!("  {:>3}. ", i + 1);
+        paint(Style::Bold, format!("{:<40}", app.name));
+        if let Some((major, minor, patch)) = get_app_minimum_system_version(&app.path) {
+            print!(" requer macOS {}.{}.{}", major, minor, patch);
+        }
+        println!();
+    }
+}
+
+fn search_apps(query: &str, mode: SearchMatchMode, recursive: bool) {
+    let matches = search_apps_with_mode(query, mode, recursive);
 
     if matches.is_empty() {
         println!("Nenhum aplicativo encontrado para: \"{}\"", query);
         return;
     }
 
-    println!(
+    header(format!(
         "=== Resultados para \"{}\" ({} encontrados) ===\n",
         query,
         matches.len()
-    );
+    ));
     for app in &matches {
         let name = app.file_stem().unwrap_or_default().to_string_lossy();
         let size = dir_size(app).unwrap_or(0);
-        println!("  - {:<40} {}", name, format_size(size));
+        print!("  - ");
+        paint(Style::Bold, format!("{:<40}", name));
+        println!(" {}", format_size(size));
     }
 }
 
-fn remove_app(app_name: &str) {
+fn remove_app(app_name: &str, opts: &RemovalOptions, config: &Config, interactive: bool) {
     let app_path = match find_app(app_name) {
         Some(p) => p,
         None => {
@@ -91,109 +484,516 @@ fn remove_app(app_name: &str) {
         }
     };
 
-    let bundle_id = get_bundle_id(&app_path);
+    if is_system_app(&app_path) {
+        eprintln!(
+            "\"{}\" e um app de sistema ({}) e nao pode ser removido.",
+            app_name,
+            app_path.display()
+        );
+        return;
+    }
+
+    let mut plan = match plan_removal_at(&app_path, opts, config) {
+        Ok(plan) => plan,
+        Err(e) => {
+            eprintln!("{}.", e);
+            return;
+        }
+    };
     let app_stem = app_path
         .file_stem()
         .unwrap_or_default()
         .to_string_lossy()
         .to_string();
 
-    let related = find_related_files(&app_stem, bundle_id.as_deref());
+    header(format!("=== Remover: {} ===\n", app_stem));
+    print!("  Aplicativo: ");
+    paint(Style::Path, app_path.display());
+    println!(" ({})", format_size(plan.app_size));
 
-    println!("=== Remover: {} ===\n", app_stem);
-    let app_size = dir_size(&app_path).unwrap_or(0);
-    println!(
-        "  Aplicativo: {} ({})",
-        app_path.display(),
-        format_size(app_size)
-    );
-
-    if let Some(ref id) = bundle_id {
+    if let Some(ref id) = plan.bundle_id {
         println!("  Bundle ID:  {}", id);
     }
 
-    if !related.is_empty() {
-        println!("\n  Arquivos residuais encontrados:");
-        let mut total_residual: u64 = 0;
-        for path in &related {
-            let size = if path.is_dir() {
-                dir_size(path).unwrap_or(0)
-            } else {
-                std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
-            };
-            total_residual += size;
-            println!("    - {} ({})", path.display(), format_size(size));
+    if !plan.related.is_empty() {
+        let (web_data, others): (Vec<_>, Vec<_>) =
+            plan.related.iter().partition(|f| f.category.is_web_data());
+        if !web_data.is_empty() {
+            println!();
+            header(format!(
+                "  Dados web (WebKit/HTTPStorages) - {}:",
+                format_size(web_data.iter().map(|f| f.size).sum())
+            ));
+            for file in &web_data {
+                print!("    * {:>10}  ", format_size(file.size));
+                paint(Style::Path, file.path.display());
+                if file.category == RelatedFileCategory::WebDataByName {
+                    print!(" (pelo nome do app)");
+                }
+                println!();
+            }
+        }
+        if !others.is_empty() {
+            println!();
+            header("  Arquivos residuais encontrados:");
+        }
+        for file in &others {
+            print!("    - ");
+            paint(Style::Path, file.path.display());
+            print!(" ({}) [{}]", format_size(file.size), file.category.label());
+            if file.requires_elevation {
+                paint(Style::Warning, " (requer sudo)");
+            }
+            println!();
+            for (path, size) in &file.contents {
+                println!("        {} ({})", display_path(path), format_size(*size));
+            }
+        }
+        if others
+            .iter()
+            .any(|f| f.category == RelatedFileCategory::Diagnostics)
+        {
+            paint(
+                Style::Warning,
+                "\n  Arquivos de diagnostico podem conter dados sensiveis do sistema.\n",
+            );
         }
         println!(
             "\n  Total a ser removido: {}",
-            format_size(app_size + total_residual)
+            format_size(plan.total_bytes)
         );
     } else {
         println!("\n  Nenhum arquivo residual encontrado.");
-        println!("  Total a ser removido: {}", format_size(app_size));
+        println!("  Total a ser removido: {}", format_size(plan.total_bytes));
     }
 
-    print!("\nDeseja continuar com a remocao? (s/N): ");
-    io::stdout().flush().unwrap();
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).unwrap();
-    if !matches!(
-        input.trim().to_lowercase().as_str(),
-        "s" | "sim" | "y" | "yes"
-    ) {
-        println!("Operacao cancelada.");
-        return;
+    // `AppIsRunning` e `HelperProcessesRunning` viram perguntas logo antes da remocao.
+    let advisories: Vec<_> = plan
+        .warnings
+        .iter()
+        .filter(|w| {
+            **w != Warning::AppIsRunning && !matches!(w, Warning::HelperProcessesRunning { .. })
+        })
+        .collect();
+    if !advisories.is_empty() {
+        println!();
+        for warning in advisories {
+            paint(Style::Warning, format!("  Aviso: {}\n", warning));
+        }
+    }
+
+    print_permission_report(&check_removal_permissions(&plan));
+
+    if interactive && !plan.related.is_empty() {
+        match select_files_interactive(&plan.related) {
+            Ok(Some(selected)) => {
+                let mut keep = selected.into_iter();
+                let related = std::mem::take(&mut plan.related);
+                plan.related = filter_related_files_by(related, |_| keep.next().unwrap_or(false));
+                plan.total_bytes = plan.app_size + plan.related.iter().map(|f| f.size).sum::<u64>();
+            }
+            Ok(None) => {
+                println!("Operacao cancelada.");
+                return;
+            }
+            Err(e) => {
+                eprintln!("Modo interativo indisponivel: {}", e);
+                return;
+            }
+        }
+        println!(
+            "\n  Selecionados: {} arquivo(s) residual(is) - total a ser removido: {}",
+            plan.related.len(),
+            format_size(plan.total_bytes)
+        );
+    } else {
+        print!("\nDeseja continuar com a remocao? (s/N): ");
+        io::stdout().flush().unwrap();
+        let mut input = String::new();
+        io::stdin().read_line(&mut input).unwrap();
+        if !matches!(
+            input.trim().to_lowercase().as_str(),
+            "s" | "sim" | "y" | "yes"
+        ) {
+            println!("Operacao cancelada.");
+            return;
+        }
     }
 
-    if is_app_running(&app_stem) {
+    if plan.warnings.contains(&Warning::AppIsRunning) {
         print!("O aplicativo esta em execucao. Deseja fecha-lo? (s/N): ");
         io::stdout().flush().unwrap();
         let mut input2 = String::new();
         io::stdin().read_line(&mut input2).unwrap();
-        if matches!(
+        if !matches!(
             input2.trim().to_lowercase().as_str(),
             "s" | "sim" | "y" | "yes"
         ) {
-            quit_app(&app_stem);
-            std::thread::sleep(std::time::Duration::from_secs(2));
-        } else {
             println!("Feche o aplicativo antes de remover.");
             return;
         }
     }
-
-    let mut errors = Vec::new();
-    print!("Removendo {}... ", app_path.display());
-    io::stdout().flush().unwrap();
-    match remove_path(&app_path) {
-        Ok(_) => println!("OK"),
-        Err(e) => {
-            println!("ERRO: {}", e);
-            errors.push(format!("{}: {}", app_path.display(), e));
+    let helpers = plan.warnings.iter().find_map(|w| match w {
+        Warning::HelperProcessesRunning { processes } => Some(processes),
+        _ => None,
+    });
+    if let Some(processes) = helpers {
+        println!("Processos do aplicativo em execucao:");
+        for path in processes {
+            println!("  {}", path.display());
+        }
+        print!("Deseja encerra-los? (s/N): ");
+        io::stdout().flush().unwrap();
+        let mut input3 = String::new();
+        io::stdin().read_line(&mut input3).unwrap();
+        if !matches!(
+            input3.trim().to_lowercase().as_str(),
+            "s" | "sim" | "y" | "yes"
+        ) {
+            println!("Encerre os processos do aplicativo antes de remover.");
+            return;
         }
     }
 
-    for path in &related {
-        print!("Removendo {}... ", path.display());
-        io::stdout().flush().unwrap();
-        match remove_path(path) {
-            Ok(_) => println!("OK"),
-            Err(e) => {
-                println!("ERRO: {}", e);
-                errors.push(format!("{}: {}", path.display(), e));
+    let total = plan.related.len() as u64 + 1;
+    let progress = MultiProgress::new();
+    let bar = progress.add(ProgressBar::new(total));
+    bar.set_style(
+        ProgressStyle::with_template(
+            "{spinner} [{bar:20}] {pos}/{len} arquivos - Removendo {wide_msg}",
+        )
+        .unwrap()
+        .progress_chars("##-"),
+    );
+    bar.enable_steady_tick(Duration::from_millis(100));
+    // Sem terminal a barra fica oculta; nesse caso mantemos uma linha por arquivo.
+    let per_file_lines = config.verbose || bar.is_hidden();
+
+    let result = execute_plan(&plan, opts, |event| match event {
+        RemovalProgress::Removing { path, .. } => bar.set_message(display_path(&path)),
+        RemovalProgress::Removed { path } => {
+            if per_file_lines {
+                progress.suspend(|| {
+                    print_removing(&path);
+                    print_ok();
+                });
+            }
+            bar.inc(1);
+        }
+        RemovalProgress::Failed { path, error } => {
+            if per_file_lines {
+                progress.suspend(|| {
+                    print_removing(&path);
+                    print_error(&error);
+                });
             }
+            bar.inc(1);
         }
+        other => progress.suspend(|| println!("{}", other)),
+    });
+    bar.finish_and_clear();
+    let result = match result {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("{}.", e);
+            return;
+        }
+    };
+    println!("{} de {} arquivos removidos.", result.removed.len(), total);
+    record_removal(&result, plan.bundle_id.clone(), opts.strategy);
+
+    for note in &result.notes {
+        paint(Style::Warning, format!("Aviso: {}\n", note));
     }
 
     println!();
-    if errors.is_empty() {
-        println!("\"{}\" removido com sucesso!", app_stem);
+    paint(Style::Bold, format!("\"{}\"", app_stem));
+    if result.is_success() {
+        println!(" removido com sucesso!");
     } else {
-        println!("\"{}\" removido com alguns erros:", app_stem);
-        for e in &errors {
-            eprintln!("  - {}", e);
+        println!(" removido com alguns erros:");
+        for (path, e) in &result.errors {
+            eprintln!("  - {}: {}", path.display(), e);
         }
-        eprintln!("\nDica: Alguns arquivos podem precisar de permissao de administrador.");
+        epaint(
+            Style::Warning,
+            "\nDica: Alguns arquivos podem precisar de permissao de administrador.\n",
+        );
         eprintln!("Tente: sudo mac-app-remover remove \"{}\"", app_name);
     }
 }
+
+/// Guarda a remocao no historico (`history` / `undo`).
+fn record_removal(result: &RemovalResult, bundle_id: Option<String>, strategy: RemovalStrategy) {
+    if result.removed.is_empty() {
+        return;
+    }
+    if let Err(e) = append_removal_record(RemovalRecord::new(result, bundle_id, strategy)) {
+        epaint(
+            Style::Warning,
+            format!("Aviso: historico nao foi salvo: {}\n", e),
+        );
+    }
+}
+
+fn show_history() {
+    let history = get_removal_history();
+    if history.is_empty() {
+        println!("Nenhuma remocao no historico.");
+        return;
+    }
+    header(format!(
+        "=== Historico de remocoes ({}) ===\n",
+        history.len()
+    ));
+    for (i, record) in history.iter().enumerate() {
+        print!("  {:>3}. {}  ", i + 1, format_timestamp(record.timestamp));
+        paint(Style::Bold, &record.app_name);
+        println!(
+            "  ({} itens, {})",
+            record.removed.len(),
+            match record.strategy {
+                RemovalStrategy::MoveToTrash => "na Lixeira",
+                RemovalStrategy::PermanentDelete => "apagados",
+            }
+        );
+    }
+}
+
+/// `undo <n>`: `n` e o numero mostrado por `history` (1 = a mais recente).
+fn undo_removal(number: &str) {
+    let history = get_removal_history();
+    let record = match number
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| history.get(n.checked_sub(1)?))
+    {
+        Some(r) => r,
+        None => {
+            eprintln!(
+                "Remocao \"{}\" nao encontrada. Veja 'mac-app-remover history'.",
+                number
+            );
+            return;
+        }
+    };
+    if record.strategy == RemovalStrategy::PermanentDelete {
+        eprintln!(
+            "\"{}\" foi apagado permanentemente; nao ha como desfazer.",
+            record.app_name
+        );
+        return;
+    }
+    header(format!("=== Desfazer: {} ===\n", record.app_name));
+    let result = match restore_from_trash(record) {
+        Ok(result) => result,
+        Err(e) => {
+            epaint(Style::Error, "ERRO: ");
+            eprintln!("{}", e);
+            return;
+        }
+    };
+    for (paths, label, style) in [
+        (&result.restored, "restaurado", Style::Ok),
+        (
+            &result.conflicts,
+            "ja existe, mantido na Lixeira",
+            Style::Warning,
+        ),
+        (&result.not_found, "nao esta na Lixeira", Style::Error),
+    ] {
+        for path in paths {
+            print!("    - ");
+            paint(Style::Path, display_path(path));
+            paint(style, format!(" ({})\n", label));
+        }
+    }
+    for (path, error) in &result.failed {
+        print!("    - ");
+        paint(Style::Path, display_path(path));
+        paint(Style::Error, format!(" (falhou: {})\n", error));
+    }
+    println!(
+        "\n{} de {} itens restaurados.",
+        result.restored.len(),
+        record.removed.len()
+    );
+}
+
+/// Problemas de permissao, antes de perguntar se deve continuar.
+fn print_permission_report(report: &PermissionReport) {
+    if !report.has_problems() && report.missing.is_empty() {
+        return;
+    }
+    println!();
+    header("  Permissoes:");
+    for (paths, label, style) in [
+        (&report.requires_sudo, "requer sudo", Style::Warning),
+        (&report.read_only, "somente leitura", Style::Error),
+        (&report.missing, "nao existe mais", Style::Bold),
+    ] {
+        for path in paths {
+            print!("    - ");
+            paint(Style::Path, display_path(path));
+            paint(style, format!(" ({})\n", label));
+        }
+    }
+}
+
+/// Restaura o terminal (modo raw e tela alternativa) mesmo em caso de erro.
+struct RawModeGuard;
+
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, cursor::Show);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Lista numerada dos arquivos residuais; digitar um numero + Enter alterna o
+/// arquivo, `y` confirma e `n` (ou Esc) cancela. Retorna `None` se cancelado,
+/// senao quais arquivos continuam marcados (todos comecam marcados).
+fn select_files_interactive(files: &[RelatedFile]) -> io::Result<Option<Vec<bool>>> {
+    let mut selected = vec![true; files.len()];
+    let mut typed = String::new();
+
+    terminal::enable_raw_mode()?;
+    let _guard = RawModeGuard;
+    let mut out = io::stdout();
+    execute!(out, EnterAlternateScreen)?;
+
+    loop {
+        // Em modo raw, `\n` nao volta o cursor para a coluna 0.
+        queue!(out, terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+        write!(
+            out,
+            "Numero + Enter alterna um arquivo, y confirma, n cancela.\r\n\r\n"
+        )?;
+        for (i, (file, on)) in files.iter().zip(&selected).enumerate() {
+            write!(
+                out,
+                "  [{}] {:>3}. {} ({}) [{}]\r\n",
+                if *on { 'x' } else { ' ' },
+                i + 1,
+                file.path.display(),
+                format_size(file.size),
+                file.category.label()
+            )?;
+        }
+        write!(out, "\r\n> {}", typed)?;
+        out.flush()?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(None),
+            KeyCode::Char(c) if c.is_ascii_digit() => typed.push(c),
+            KeyCode::Backspace => {
+                typed.pop();
+            }
+            KeyCode::Enter => {
+                if let Ok(n) = typed.parse::<usize>() {
+                    if let Some(on) = n.checked_sub(1).and_then(|i| selected.get_mut(i)) {
+                        *on = !*on;
+                    }
+                }
+                typed.clear();
+            }
+            KeyCode::Char('y') | KeyCode::Char('Y') => return Ok(Some(selected)),
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+/// Caminho com `~` no lugar do diretorio home, para caber na barra de progresso.
+fn display_path(path: &Path) -> String {
+    match path.strip_prefix(get_home()) {
+        Ok(rest) => format!("~/{}", rest.display()),
+        Err(_) => path.display().to_string(),
+    }
+}
+
+fn print_removing(path: &Path) {
+    print!("Removendo ");
+    paint(Style::Path, path.display());
+    print!("... ");
+    io::stdout().flush().unwrap();
+}
+
+fn print_ok() {
+    paint(Style::Ok, "OK");
+    println!();
+}
+
+fn print_error(e: impl fmt::Display) {
+    paint(Style::Error, "ERRO:");
+    println!(" {}", e);
+}
+
+fn scan_orphans_containers() {
+    let orphans = scan_orphaned_containers();
+    if orphans.is_empty() {
+        println!("Nenhum container orfao encontrado.");
+        return;
+    }
+    let sizes: Vec<u64> = orphans
+        .iter()
+        .map(|(path, _)| dir_size(path).unwrap_or(0))
+        .collect();
+    header(format!(
+        "=== Containers orfaos ({}) - {} ===\n",
+        orphans.len(),
+        format_size(sizes.iter().sum())
+    ));
+    for ((path, bundle_id), size) in orphans.iter().zip(&sizes) {
+        print!("  - ");
+        paint(Style::Bold, format!("{:<50}", bundle_id));
+        print!(" {:>10}  ", format_size(*size));
+        paint(Style::Path, path.display());
+        println!();
+    }
+}
+
+fn remove_app_safe(app_name: &str) {
+    header(format!("=== Remover (modo seguro): {} ===\n", app_name));
+    let result = match remove_app_safe_mode(app_name) {
+        Ok(r) => r,
+        Err(RemoverError::AppNotFound(_)) => {
+            eprintln!("Aplicativo \"{}\" nao encontrado.", app_name);
+            eprintln!("Use 'mac-app-remover search {}' para buscar.", app_name);
+            return;
+        }
+        Err(e) => {
+            println!("{}.", e);
+            return;
+        }
+    };
+
+    record_removal(&result, None, RemovalStrategy::PermanentDelete);
+
+    println!();
+    println!(
+        "Removidos: {}  Pulados: {}  Erros: {}",
+        result.removed.len(),
+        result.skipped.len(),
+        result.errors.len()
+    );
+    for note in &result.notes {
+        paint(Style::Warning, format!("Aviso: {}\n", note));
+    }
+    if !result.is_success() {
+        for (path, e) in &result.errors {
+            eprintln!("  - {}: {}", path.display(), e);
+        }
+        epaint(
+            Style::Warning,
+            "\nDica: Alguns arquivos podem precisar de permissao de administrador.\n",
+        );
+        eprintln!("Tente: sudo mac-app-remover remove --safe \"{}\"", app_name);
+    }
+}
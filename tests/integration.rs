@@ -0,0 +1,169 @@
+//! Testes de ponta a ponta com bundles `.app` falsos em diretorios temporarios.
+//! Nada fora do `tempdir` e lido ou alterado.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use mac_app_remover::*;
+use tempfile::TempDir;
+
+/// Cria `<dir>/<name>.app` com um Info.plist contendo `bundle_id`.
+fn fake_app(dir: &Path, name: &str, bundle_id: &str) -> PathBuf {
+    let app = dir.join(format!("{}.app", name));
+    let contents = app.join("Contents");
+    fs::create_dir_all(contents.join("MacOS")).unwrap();
+    fs::write(
+        contents.join("Info.plist"),
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>CFBundleIdentifier</key>
+    <string>{}</string>
+    <key>CFBundleName</key>
+    <string>{}</string>
+    <key>CFBundleShortVersionString</key>
+    <string>2.10.1</string>
+    <key>LSMinimumSystemVersion</key>
+    <string>11.0</string>
+</dict>
+</plist>
+"#,
+            bundle_id, name
+        ),
+    )
+    .unwrap();
+    fs::write(contents.join("MacOS").join(name), [0u8; 100]).unwrap();
+    app
+}
+
+#[test]
+fn lists_fake_apps_from_injected_dirs() {
+    let tmp = TempDir::new().unwrap();
+    let apps_dir = tmp.path().join("Applications");
+    fake_app(&apps_dir, "Zeta", "com.example.zeta");
+    fake_app(&apps_dir, "alpha", "com.example.alpha");
+    fake_app(&apps_dir.join("Utilities"), "Nested", "com.example.nested");
+
+    let names = |apps: Vec<PathBuf>| -> Vec<String> {
+        apps.iter()
+            .map(|a| a.file_stem().unwrap().to_string_lossy().to_string())
+            .collect()
+    };
+    let dirs = [apps_dir];
+    assert_eq!(
+        names(get_installed_apps_in(&dirs, false)),
+        ["alpha", "Zeta"]
+    );
+    assert_eq!(
+        names(get_installed_apps_in(&dirs, true)),
+        ["alpha", "Nested", "Zeta"]
+    );
+}
+
+#[test]
+fn reads_bundle_id_and_size_of_fake_app() {
+    let tmp = TempDir::new().unwrap();
+    let app = fake_app(tmp.path(), "MyApp", "com.example.myapp");
+
+    assert_eq!(get_bundle_id(&app).as_deref(), Some("com.example.myapp"));
+    assert_eq!(get_bundle_id(&tmp.path().join("Missing.app")), None);
+    assert_eq!(get_app_version(&app).as_deref(), Some("2.10.1"));
+    assert_eq!(get_info_plist_path(&app), app.join("Contents/Info.plist"));
+    assert_eq!(
+        get_main_executable_path(&app),
+        Some(app.join("Contents/MacOS/MyApp"))
+    );
+    assert!(get_app_version_from_plist(&app) > AppVersion::parse("2.9"));
+
+    let plist_len = fs::metadata(app.join("Contents/Info.plist")).unwrap().len();
+    assert_eq!(dir_size(&app).unwrap(), plist_len + 100);
+}
+
+#[test]
+fn finds_and_removes_related_files_under_fake_home() {
+    let tmp = TempDir::new().unwrap();
+    let home = tmp.path();
+    let lib = home.join("Library");
+    let support = lib.join("Application Support/MyApp");
+    let cache = lib.join("Caches/com.example.myapp");
+    fs::create_dir_all(&support).unwrap();
+    fs::create_dir_all(&cache).unwrap();
+    fs::write(support.join("data.db"), [0u8; 42]).unwrap();
+    fs::create_dir_all(lib.join("Caches/com.example.other")).unwrap();
+
+    let related =
+        find_related_files_in(home, "MyApp", Some("com.example.myapp"), &Config::default());
+    let mut paths: Vec<_> = related.iter().map(|f| f.path.clone()).collect();
+    paths.sort();
+    assert_eq!(paths, [support.clone(), cache.clone()]);
+    let support_entry = related.iter().find(|f| f.path == support).unwrap();
+    assert_eq!(support_entry.size, 42);
+    // Bancos de dados em Application Support aparecem dentro da pasta.
+    assert_eq!(support_entry.contents, [(support.join("data.db"), 42)]);
+
+    for file in &related {
+        remove_path(&file.path).unwrap();
+    }
+    assert!(!support.exists());
+    assert!(!cache.exists());
+    assert!(lib.join("Caches/com.example.other").exists());
+}
+
+#[test]
+fn remove_path_handles_files_and_dirs() {
+    let tmp = TempDir::new().unwrap();
+    let app = fake_app(tmp.path(), "Gone", "com.example.gone");
+    let file = tmp.path().join("leftover.plist");
+    fs::write(&file, "x").unwrap();
+
+    remove_path(&app).unwrap();
+    remove_path(&file).unwrap();
+    assert!(!app.exists());
+    assert!(!file.exists());
+    assert!(remove_path(&file).is_err());
+}
+
+#[test]
+fn fake_app_is_not_running() {
+    let tmp = TempDir::new().unwrap();
+    let name = format!("MacAppRemoverTest{}", std::process::id());
+    fake_app(tmp.path(), &name, "com.example.notrunning");
+    assert!(!is_app_running(&name));
+}
+
+#[test]
+fn compares_fake_app_against_expected_values() {
+    let tmp = TempDir::new().unwrap();
+    let app = fake_app(tmp.path(), "Tool", "com.example.tool");
+
+    assert_eq!(
+        compare_installed_vs_expected(&app, "com.example.tool", "2.10.1"),
+        ComparisonResult::Match
+    );
+    assert_eq!(
+        compare_installed_vs_expected(&app, "com.example.other", "2.10.1"),
+        ComparisonResult::BundleIdMismatch
+    );
+    assert_eq!(
+        compare_installed_vs_expected(&app, "com.example.tool", "2.9"),
+        ComparisonResult::VersionMismatch {
+            found: "2.10.1".to_string(),
+            expected: "2.9".to_string()
+        }
+    );
+
+    assert_eq!(get_app_minimum_system_version(&app), Some((11, 0, 0)));
+    let info = get_app_info_extended(&app).unwrap();
+    assert_eq!(info.bundle_id.as_deref(), Some("com.example.tool"));
+    assert_eq!(info.version(), Some("2.10.1"));
+    assert_eq!(info.minimum_system_version.as_deref(), Some("11.0"));
+    assert_eq!(info.executable, None);
+
+    fs::remove_file(app.join("Contents/MacOS/Tool")).unwrap();
+    assert_eq!(
+        compare_installed_vs_expected(&app, "com.example.tool", "2.10.1"),
+        ComparisonResult::MissingExecutable
+    );
+}
@@ -0,0 +1,48 @@
+//! Testes de propriedade para `normalize_app_name` e a busca de `find_app_in`.
+
+use std::fs;
+
+use mac_app_remover::{find_app_in, normalize_app_name};
+use proptest::prelude::*;
+use unicode_normalization::UnicodeNormalization;
+
+proptest! {
+    #[test]
+    fn normalize_is_idempotent(s in any::<String>()) {
+        let once = normalize_app_name(&s);
+        prop_assert_eq!(normalize_app_name(&once), once);
+    }
+
+    #[test]
+    fn normalize_ignores_case_and_normalization_form(s in "\\PC{0,30}") {
+        let nfd: String = s.nfd().collect();
+        prop_assert_eq!(normalize_app_name(&nfd), normalize_app_name(&s));
+
+        let upper = s.to_uppercase();
+        // ex: "ß" vira "SS", que nao volta a ser "ß"
+        prop_assume!(upper.to_lowercase() == s.to_lowercase());
+        prop_assert_eq!(normalize_app_name(&upper), normalize_app_name(&s));
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn find_app_matches_across_case_and_normalization(
+        name in "[A-Za-z][A-Za-z0-9àáâãçéêíóôõúüÀÉÇÕ ]{0,20}[A-Za-z0-9àéçõ]",
+        nested in any::<bool>(),
+    ) {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let apps = tmp.path().join("Applications");
+        // O bundle fica gravado em NFD, como o Finder faz.
+        let parent = if nested { apps.join("Utilities") } else { apps.clone() };
+        let on_disk = parent.join(format!("{}.app", name.nfd().collect::<String>()));
+        fs::create_dir_all(&on_disk).unwrap();
+
+        let dirs = [apps];
+        let query: String = name.nfc().collect::<String>().to_lowercase();
+        prop_assert_eq!(find_app_in(&dirs, &query), Some(on_disk.clone()));
+        prop_assert_eq!(find_app_in(&dirs, &normalize_app_name(&name)), Some(on_disk));
+    }
+}